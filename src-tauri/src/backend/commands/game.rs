@@ -2,8 +2,22 @@ use crate::backend::services::launch_service::{self, LaunchModdedArgs, LaunchVan
 use crate::backend::services::xbox_service::{
     self, XboxCleanupArgs, XboxLaunchArgs, XboxPrepareLaunchArgs,
 };
+use crate::backend::state::game_runtime::{self, GameInstance, GameStopResult};
+use tauri::async_runtime::spawn_blocking;
 use tauri::{AppHandle, Runtime};
 
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameStopArgs {
+    pub pid: u32,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameStopAllArgs {
+    pub profile_id: Option<String>,
+}
+
 #[tauri::command]
 pub async fn game_launch_modded<R: Runtime>(
     app: AppHandle<R>,
@@ -46,3 +60,30 @@ pub async fn game_xbox_launch<R: Runtime>(
 pub async fn game_xbox_cleanup(args: XboxCleanupArgs) -> Result<(), String> {
     xbox_service::cleanup_xbox_files(args).map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn game_list_instances() -> Result<Vec<GameInstance>, String> {
+    game_runtime::list_instances().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn game_stop<R: Runtime>(
+    app: AppHandle<R>,
+    args: GameStopArgs,
+) -> Result<GameStopResult, String> {
+    spawn_blocking(move || game_runtime::stop_game(&app, args.pid))
+        .await
+        .map_err(|e| format!("Game stop task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn game_stop_all<R: Runtime>(
+    app: AppHandle<R>,
+    args: GameStopAllArgs,
+) -> Result<Vec<GameStopResult>, String> {
+    spawn_blocking(move || game_runtime::stop_all_games(&app, args.profile_id.as_deref()))
+        .await
+        .map_err(|e| format!("Game stop task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
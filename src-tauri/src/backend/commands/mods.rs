@@ -1,13 +1,21 @@
-use crate::backend::services::bepinex_service;
-use crate::backend::services::mod_download_service;
-use tauri::{AppHandle, Runtime};
+use crate::backend::services::bepinex_service::{self, BepInExInstallRequest};
+use crate::backend::services::http_retry::RetryPolicy;
+use crate::backend::services::mod_cache_service::{self, ModCacheGcResult, ModCacheUsage};
+use crate::backend::services::mod_dependency_service::{self, InstallPlan, ResolveRequest};
+use crate::backend::services::mod_download_service::{self, ModDownloadRequest};
+use crate::backend::services::snapshot_service;
+use crate::backend::services::storage_usage_service::{self, StorageLocations, StorageUsageReport};
+use crate::backend::state::archive_operations::CancellationToken;
+use crate::backend::state::download_queue::{self, DownloadQueuePayload, DownloadRequest};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Manager, Runtime};
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModdingBepInExInstallArgs {
-    pub url: String,
-    pub destination: String,
-    pub cache_path: Option<String>,
+    #[serde(flatten)]
+    pub install: BepInExInstallRequest,
+    pub max_attempts: Option<u32>,
 }
 
 #[derive(serde::Deserialize)]
@@ -15,6 +23,7 @@ pub struct ModdingBepInExInstallArgs {
 pub struct ModdingBepInExCacheDownloadArgs {
     pub url: String,
     pub cache_path: String,
+    pub max_attempts: Option<u32>,
 }
 
 #[derive(serde::Deserialize)]
@@ -32,10 +41,30 @@ pub struct ModdingBepInExCacheExistsArgs {
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModdingModDownloadArgs {
-    pub mod_id: String,
-    pub url: String,
-    pub destination: String,
-    pub expected_checksum: String,
+    #[serde(flatten)]
+    pub download: ModDownloadRequest,
+    pub max_attempts: Option<u32>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModdingDownloadEnqueueArgs {
+    pub request: DownloadRequest,
+    #[serde(default)]
+    pub priority: bool,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModdingDownloadCancelArgs {
+    pub job_id: Option<String>,
+    pub mod_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModdingDownloadSetParallelismArgs {
+    pub max_parallel: usize,
 }
 
 #[tauri::command]
@@ -43,9 +72,15 @@ pub async fn modding_bepinex_install<R: Runtime>(
     app: AppHandle<R>,
     args: ModdingBepInExInstallArgs,
 ) -> Result<(), String> {
-    bepinex_service::install_bepinex(app, args.url, args.destination, args.cache_path)
-        .await
-        .map_err(|e| e.to_string())
+    bepinex_service::install_bepinex(
+        app,
+        args.install,
+        RetryPolicy::with_max_attempts(args.max_attempts),
+        None,
+        &CancellationToken::default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -53,9 +88,14 @@ pub async fn modding_bepinex_cache_download<R: Runtime>(
     app: AppHandle<R>,
     args: ModdingBepInExCacheDownloadArgs,
 ) -> Result<(), String> {
-    bepinex_service::download_bepinex_to_cache(app, args.url, args.cache_path)
-        .await
-        .map_err(|e| e.to_string())
+    bepinex_service::download_bepinex_to_cache(
+        app,
+        args.url,
+        args.cache_path,
+        RetryPolicy::with_max_attempts(args.max_attempts),
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -77,11 +117,90 @@ pub async fn modding_mod_download<R: Runtime>(
 ) -> Result<(), String> {
     mod_download_service::download_mod(
         app,
-        args.mod_id,
-        args.url,
-        args.destination,
-        args.expected_checksum,
+        args.download,
+        RetryPolicy::with_max_attempts(args.max_attempts),
+        None,
+        &CancellationToken::default(),
     )
     .await
     .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn modding_download_enqueue<R: Runtime>(
+    app: AppHandle<R>,
+    args: ModdingDownloadEnqueueArgs,
+) -> Result<String, String> {
+    download_queue::enqueue(&app, args.request, args.priority).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_download_cancel<R: Runtime>(
+    app: AppHandle<R>,
+    args: ModdingDownloadCancelArgs,
+) -> Result<usize, String> {
+    download_queue::cancel(&app, args.job_id.as_deref(), args.mod_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_download_cancel_all<R: Runtime>(app: AppHandle<R>) -> Result<usize, String> {
+    download_queue::cancel_all(&app).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_download_list() -> Result<DownloadQueuePayload, String> {
+    download_queue::list_jobs().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_download_set_parallelism<R: Runtime>(
+    app: AppHandle<R>,
+    args: ModdingDownloadSetParallelismArgs,
+) -> Result<(), String> {
+    download_queue::set_max_parallel(&app, args.max_parallel).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_mod_cache_usage<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<ModCacheUsage, String> {
+    let cache_dir = mod_cache_service::cache_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || mod_cache_service::cache_usage(&cache_dir))
+        .await
+        .map_err(|e| format!("Cache usage task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn storage_usage_report<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<StorageUsageReport, String> {
+    let locations = StorageLocations {
+        app_data_dir: app.path().app_data_dir().map_err(|e| e.to_string())?,
+        profiles_dir: mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?,
+        mod_cache_dir: mod_cache_service::cache_dir(&app).map_err(|e| e.to_string())?,
+        snapshots_dir: snapshot_service::snapshots_dir(&app).map_err(|e| e.to_string())?,
+    };
+    spawn_blocking(move || storage_usage_service::usage_report(&locations))
+        .await
+        .map_err(|e| format!("Storage usage task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_mod_cache_gc<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<ModCacheGcResult, String> {
+    let cache_dir = mod_cache_service::cache_dir(&app).map_err(|e| e.to_string())?;
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || mod_cache_service::collect_garbage(&cache_dir, &profiles_dir))
+        .await
+        .map_err(|e| format!("Cache GC task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn modding_resolve_dependencies(args: ResolveRequest) -> Result<InstallPlan, String> {
+    mod_dependency_service::resolve_install(&args).map_err(|e| e.to_string())
+}
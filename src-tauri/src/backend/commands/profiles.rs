@@ -1,11 +1,31 @@
-use crate::backend::services::profile_zip_service::{self, ProfileImportResult};
+use crate::backend::services::extraction_policy::ExtractionPolicy;
+use crate::backend::services::mod_cache_service;
+use crate::backend::services::play_time_service::{self, PlaySession};
+use crate::backend::services::profile_clone_service::{self, ProfileCloneResult};
+use crate::backend::services::profile_code_service;
+use crate::backend::services::profile_integrity_service::{
+    self, IntegrityRepairResult, IntegrityReport,
+};
+use crate::backend::services::profile_manifest_service::{
+    self, ManifestExportResult, ManifestImportResult,
+};
+use crate::backend::services::profile_service::{self, Profile};
+use crate::backend::services::profile_zip_service::{
+    self, ArchiveTracker, ProfileImportMode, ProfileImportResult, ProfileZipInspection,
+};
+use crate::backend::services::snapshot_service::{self, SnapshotInfo, SnapshotReason};
+use crate::backend::state::archive_operations::{self, ArchiveProgress};
+use log::warn;
+use std::path::PathBuf;
 use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Runtime};
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProfilesExportZipArgs {
     pub profile_path: String,
     pub destination: String,
+    pub operation_id: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -13,12 +33,151 @@ pub struct ProfilesExportZipArgs {
 pub struct ProfilesImportZipArgs {
     pub zip_path: String,
     pub destination: String,
+    #[serde(default)]
+    pub mode: ProfileImportMode,
+    pub operation_id: Option<String>,
+    #[serde(default)]
+    pub extraction_policy: ExtractionPolicy,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesArchiveCancelArgs {
+    pub operation_id: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesInspectZipArgs {
+    pub zip_path: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesExportManifestArgs {
+    pub profile_path: String,
+    pub destination: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesImportManifestArgs {
+    pub zip_path: String,
+    pub destination: String,
+    /// Base URL of the mod API that manifest entries are resolved against.
+    pub api_base_url: String,
+    #[serde(default)]
+    pub extraction_policy: ExtractionPolicy,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesExportCodeArgs {
+    pub profile_path: String,
+    #[serde(default)]
+    pub config_files: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesImportCodeArgs {
+    pub code: String,
+    pub destination: String,
+    pub api_base_url: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesCloneArgs {
+    pub profile_path: String,
+    pub name: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesLockVerifyArgs {
+    pub profile_path: String,
+    #[serde(default)]
+    pub repair: bool,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesRepairArgs {
+    pub profile_path: String,
+    #[serde(default)]
+    pub remove_unrecoverable: bool,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesSnapshotCreateArgs {
+    pub profile_path: String,
+    pub label: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesSnapshotBatchArgs {
+    pub profile_path: String,
+    pub reason: SnapshotReason,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesSnapshotListArgs {
+    pub profile_path: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesSnapshotArgs {
+    pub profile_path: String,
+    pub snapshot_id: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesIdArgs {
+    pub profile_id: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesCreateArgs {
+    pub name: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesUpdateArgs {
+    pub profile: Profile,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesRenameArgs {
+    pub profile_id: String,
+    pub name: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesPathArgs {
+    pub profile_path: String,
 }
 
 #[tauri::command]
-pub async fn profiles_export_zip(args: ProfilesExportZipArgs) -> Result<(), String> {
+pub async fn profiles_export_zip<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesExportZipArgs,
+) -> Result<(), String> {
+    let operation = archive_operations::register(&app, "export", args.operation_id)
+        .map_err(|e| e.to_string())?;
     spawn_blocking(move || {
-        profile_zip_service::export_profile_zip(args.profile_path, args.destination)
+        let mut on_progress = |progress: &ArchiveProgress| operation.emit(progress);
+        let mut tracker = ArchiveTracker::new(&operation.token, &mut on_progress);
+        profile_zip_service::export_profile_zip(args.profile_path, args.destination, &mut tracker)
     })
     .await
     .map_err(|e| format!("Export task failed: {e}"))?
@@ -26,11 +185,366 @@ pub async fn profiles_export_zip(args: ProfilesExportZipArgs) -> Result<(), Stri
 }
 
 #[tauri::command]
-pub async fn profiles_import_zip(
+pub async fn profiles_import_zip<R: Runtime>(
+    app: AppHandle<R>,
     args: ProfilesImportZipArgs,
 ) -> Result<ProfileImportResult, String> {
-    spawn_blocking(move || profile_zip_service::import_profile_zip(args.zip_path, args.destination))
+    let operation = archive_operations::register(&app, "import", args.operation_id)
+        .map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        let mut on_progress = |progress: &ArchiveProgress| operation.emit(progress);
+        let mut tracker = ArchiveTracker::new(&operation.token, &mut on_progress);
+        profile_zip_service::import_profile_zip(
+            args.zip_path,
+            args.destination,
+            &args.extraction_policy,
+            args.mode,
+            &mut tracker,
+        )
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn profiles_archive_cancel(args: ProfilesArchiveCancelArgs) -> Result<bool, String> {
+    archive_operations::cancel(&args.operation_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_inspect_zip(
+    args: ProfilesInspectZipArgs,
+) -> Result<ProfileZipInspection, String> {
+    spawn_blocking(move || profile_zip_service::inspect_profile_zip(args.zip_path))
+        .await
+        .map_err(|e| format!("Inspect task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_export_manifest(
+    args: ProfilesExportManifestArgs,
+) -> Result<ManifestExportResult, String> {
+    spawn_blocking(move || {
+        profile_manifest_service::export_profile_manifest(args.profile_path, args.destination)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_import_manifest<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesImportManifestArgs,
+) -> Result<ManifestImportResult, String> {
+    let zip_path = args.zip_path.clone();
+    let destination = args.destination.clone();
+    let policy = args.extraction_policy.clone();
+    let (manifest, metadata_name) = spawn_blocking(move || {
+        profile_manifest_service::extract_profile_manifest(&zip_path, &destination, &policy)
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {e}"))?
+    .map_err(|e| e.to_string())?;
+
+    profile_manifest_service::install_manifest_mods(
+        &app,
+        &args.api_base_url,
+        &manifest.mods,
+        &args.destination,
+        metadata_name,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_export_code(args: ProfilesExportCodeArgs) -> Result<String, String> {
+    spawn_blocking(move || {
+        let payload = profile_code_service::build_payload(
+            &PathBuf::from(&args.profile_path),
+            &args.config_files,
+        )?;
+        profile_code_service::encode_payload(&payload)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_import_code<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesImportCodeArgs,
+) -> Result<ManifestImportResult, String> {
+    let payload = profile_code_service::decode_payload(&args.code).map_err(|e| e.to_string())?;
+    let mods = payload.mods.clone();
+    let destination = PathBuf::from(&args.destination);
+    let metadata_name =
+        spawn_blocking(move || profile_code_service::write_payload(&payload, &destination))
+            .await
+            .map_err(|e| format!("Import task failed: {e}"))?
+            .map_err(|e| e.to_string())?;
+
+    profile_manifest_service::install_manifest_mods(
+        &app,
+        &args.api_base_url,
+        &mods,
+        &args.destination,
+        metadata_name,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_clone<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesCloneArgs,
+) -> Result<ProfileCloneResult, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        profile_clone_service::clone_profile(
+            &profiles_dir,
+            &PathBuf::from(&args.profile_path),
+            &args.name,
+        )
+    })
+    .await
+    .map_err(|e| format!("Clone task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_snapshot_create<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesSnapshotCreateArgs,
+) -> Result<SnapshotInfo, String> {
+    let store = snapshot_service::snapshots_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        snapshot_service::create_snapshot(
+            &store,
+            &PathBuf::from(&args.profile_path),
+            SnapshotReason::Manual,
+            args.label,
+        )
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+/// Called once before a batch of mod installs or removals.
+#[tauri::command]
+pub async fn profiles_snapshot_batch<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesSnapshotBatchArgs,
+) -> Result<SnapshotInfo, String> {
+    if !matches!(
+        args.reason,
+        SnapshotReason::BeforeModInstall | SnapshotReason::BeforeModRemoval
+    ) {
+        return Err(format!("{:?} is not a batch snapshot reason", args.reason));
+    }
+
+    let store = snapshot_service::snapshots_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        snapshot_service::batch_snapshot(&store, &PathBuf::from(&args.profile_path), args.reason)
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_snapshot_list<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesSnapshotListArgs,
+) -> Result<Vec<SnapshotInfo>, String> {
+    let store = snapshot_service::snapshots_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        snapshot_service::list_snapshots(&store, &PathBuf::from(&args.profile_path))
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_snapshot_restore<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesSnapshotArgs,
+) -> Result<SnapshotInfo, String> {
+    let store = snapshot_service::snapshots_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        snapshot_service::restore_snapshot(
+            &store,
+            &PathBuf::from(&args.profile_path),
+            &args.snapshot_id,
+        )
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_snapshot_delete<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesSnapshotArgs,
+) -> Result<(), String> {
+    let store = snapshot_service::snapshots_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        snapshot_service::delete_snapshot(
+            &store,
+            &PathBuf::from(&args.profile_path),
+            &args.snapshot_id,
+        )
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_list<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Profile>, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        if let Err(e) = profile_service::migrate_legacy_registry(&app, &profiles_dir) {
+            warn!("Legacy profile migration failed: {}", e);
+        }
+        profile_service::list_profiles(&profiles_dir)
+    })
+    .await
+    .map_err(|e| format!("Profile task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_get<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesIdArgs,
+) -> Result<Profile, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || profile_service::get_profile(&profiles_dir, &args.profile_id))
+        .await
+        .map_err(|e| format!("Profile task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_create<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesCreateArgs,
+) -> Result<Profile, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || profile_service::create_profile(&profiles_dir, &args.name))
+        .await
+        .map_err(|e| format!("Profile task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_update<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesUpdateArgs,
+) -> Result<Profile, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || profile_service::update_profile(&profiles_dir, args.profile))
+        .await
+        .map_err(|e| format!("Profile task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_register_import<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesUpdateArgs,
+) -> Result<Profile, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || profile_service::register_imported_profile(&profiles_dir, args.profile))
+        .await
+        .map_err(|e| format!("Profile task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_rename<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesRenameArgs,
+) -> Result<Profile, String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || {
+        profile_service::rename_profile(&profiles_dir, &args.profile_id, &args.name)
+    })
+    .await
+    .map_err(|e| format!("Profile task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_delete<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesIdArgs,
+) -> Result<(), String> {
+    let profiles_dir = mod_cache_service::profiles_dir(&app).map_err(|e| e.to_string())?;
+    spawn_blocking(move || profile_service::delete_profile(&profiles_dir, &args.profile_id))
+        .await
+        .map_err(|e| format!("Profile task failed: {e}"))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_verify(args: ProfilesPathArgs) -> Result<IntegrityReport, String> {
+    spawn_blocking(move || {
+        profile_integrity_service::verify_profile(&PathBuf::from(&args.profile_path))
+    })
+    .await
+    .map_err(|e| format!("Verification task failed: {e}"))?
+    .map_err(|e| e.to_string())
+}
+
+/// Verifies a profile against its lockfile and metadata, optionally repairing
+/// it first. Repairs reinstall missing and modified mods; lock entries for mods
+/// no longer in `metadata.json` are pruned rather than reinstalled.
+#[tauri::command]
+pub async fn profiles_lock_verify<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesLockVerifyArgs,
+) -> Result<IntegrityReport, String> {
+    let profile_dir = PathBuf::from(&args.profile_path);
+    if args.repair {
+        let result = profile_integrity_service::repair_profile(&app, &profile_dir, false)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(result.report);
+    }
+    profiles_verify(ProfilesPathArgs {
+        profile_path: args.profile_path,
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn profiles_repair<R: Runtime>(
+    app: AppHandle<R>,
+    args: ProfilesRepairArgs,
+) -> Result<IntegrityRepairResult, String> {
+    profile_integrity_service::repair_profile(
+        &app,
+        &PathBuf::from(&args.profile_path),
+        args.remove_unrecoverable,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn profiles_play_sessions(args: ProfilesPathArgs) -> Result<Vec<PlaySession>, String> {
+    spawn_blocking(move || play_time_service::list_sessions(&PathBuf::from(&args.profile_path)))
         .await
-        .map_err(|e| format!("Import task failed: {e}"))?
+        .map_err(|e| format!("Play session task failed: {e}"))?
         .map_err(|e| e.to_string())
 }
@@ -6,6 +6,7 @@ pub type AppResult<T> = Result<T, AppError>;
 pub enum AppError {
     Io(std::io::Error),
     Http(reqwest::Error),
+    HttpStatus(reqwest::StatusCode, Option<std::time::Duration>),
     Zip(zip::result::ZipError),
     Parse(String),
     Process(String),
@@ -21,6 +22,13 @@ impl AppError {
         Self::Parse(message.into())
     }
 
+    pub fn http_status(
+        status: reqwest::StatusCode,
+        retry_after: Option<std::time::Duration>,
+    ) -> Self {
+        Self::HttpStatus(status, retry_after)
+    }
+
     pub fn process(message: impl Into<String>) -> Self {
         Self::Process(message.into())
     }
@@ -51,6 +59,7 @@ impl Display for AppError {
         match self {
             Self::Io(e) => write!(f, "I/O error: {e}"),
             Self::Http(e) => write!(f, "HTTP error: {e}"),
+            Self::HttpStatus(status, _) => write!(f, "Download failed: HTTP {status}"),
             Self::Zip(e) => write!(f, "Zip error: {e}"),
             Self::Parse(e) => write!(f, "Parse error: {e}"),
             Self::Process(e) => write!(f, "Process error: {e}"),
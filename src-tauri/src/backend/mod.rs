@@ -63,6 +63,9 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::game::game_launch_modded,
             commands::game::game_launch_vanilla,
+            commands::game::game_list_instances,
+            commands::game::game_stop,
+            commands::game::game_stop_all,
             commands::game::game_xbox_get_app_id,
             commands::game::game_xbox_prepare_launch,
             commands::game::game_xbox_launch,
@@ -74,8 +77,40 @@ pub fn run() {
             commands::mods::modding_bepinex_cache_clear,
             commands::mods::modding_bepinex_cache_exists,
             commands::mods::modding_mod_download,
+            commands::mods::modding_download_enqueue,
+            commands::mods::modding_download_cancel,
+            commands::mods::modding_download_cancel_all,
+            commands::mods::modding_download_list,
+            commands::mods::modding_download_set_parallelism,
+            commands::mods::modding_mod_cache_usage,
+            commands::mods::modding_mod_cache_gc,
+            commands::mods::storage_usage_report,
+            commands::mods::modding_resolve_dependencies,
+            commands::profiles::profiles_list,
+            commands::profiles::profiles_get,
+            commands::profiles::profiles_create,
+            commands::profiles::profiles_update,
+            commands::profiles::profiles_rename,
+            commands::profiles::profiles_register_import,
+            commands::profiles::profiles_delete,
             commands::profiles::profiles_export_zip,
             commands::profiles::profiles_import_zip,
+            commands::profiles::profiles_inspect_zip,
+            commands::profiles::profiles_archive_cancel,
+            commands::profiles::profiles_lock_verify,
+            commands::profiles::profiles_verify,
+            commands::profiles::profiles_repair,
+            commands::profiles::profiles_play_sessions,
+            commands::profiles::profiles_clone,
+            commands::profiles::profiles_export_manifest,
+            commands::profiles::profiles_import_manifest,
+            commands::profiles::profiles_export_code,
+            commands::profiles::profiles_import_code,
+            commands::profiles::profiles_snapshot_create,
+            commands::profiles::profiles_snapshot_batch,
+            commands::profiles::profiles_snapshot_list,
+            commands::profiles::profiles_snapshot_restore,
+            commands::profiles::profiles_snapshot_delete,
             commands::epic::epic_auth_url,
             commands::epic::epic_login_code,
             commands::epic::epic_login_webview,
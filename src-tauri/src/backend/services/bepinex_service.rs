@@ -1,67 +1,134 @@
 use crate::backend::error::AppResult;
+use crate::backend::services::extraction_policy::ExtractionPolicy;
 use crate::backend::services::http_download::{download_file, extract_zip};
+use crate::backend::services::http_retry::{RetryNotice, RetryPolicy};
+use crate::backend::services::snapshot_service::{self, SnapshotReason};
+use crate::backend::state::archive_operations::CancellationToken;
 use log::{debug, info, warn};
 use std::fs;
 use std::path::Path;
 use tauri::{AppHandle, Emitter, Runtime};
 
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BepInExInstallRequest {
+    pub url: String,
+    pub destination: String,
+    pub cache_path: Option<String>,
+    #[serde(default)]
+    pub extraction_policy: ExtractionPolicy,
+}
+
 #[derive(Clone, serde::Serialize)]
 pub struct BepInExProgress {
     stage: String,
     progress: f64,
     message: String,
+    job_id: Option<String>,
 }
 
-fn emit<R: Runtime>(app: &AppHandle<R>, stage: &str, progress: f64, message: &str) {
-    let _ = app.emit(
-        "bepinex-progress",
-        BepInExProgress {
-            stage: stage.to_string(),
-            progress,
-            message: message.to_string(),
-        },
-    );
+struct ProgressEmitter<'a, R: Runtime> {
+    app: &'a AppHandle<R>,
+    job_id: Option<&'a str>,
+}
+
+impl<R: Runtime> ProgressEmitter<'_, R> {
+    fn emit(&self, stage: &str, progress: f64, message: &str) {
+        let _ = self.app.emit(
+            "bepinex-progress",
+            BepInExProgress {
+                stage: stage.to_string(),
+                progress,
+                message: message.to_string(),
+                job_id: self.job_id.map(str::to_string),
+            },
+        );
+    }
+
+    fn emit_retry(&self, notice: &RetryNotice) {
+        self.emit(
+            "retrying",
+            0.0,
+            &format!(
+                "Download failed, retrying ({}/{}) in {}s...",
+                notice.attempt + 1,
+                notice.max_attempts,
+                notice.delay.as_secs().max(1)
+            ),
+        );
+    }
+}
+
+/// Removes the downloaded archive however the install ends, including when it
+/// is cancelled or fails part-way.
+struct StagedArchive<'a>(&'a Path);
+
+impl Drop for StagedArchive<'_> {
+    fn drop(&mut self) {
+        fs::remove_file(self.0).ok();
+    }
 }
 
 pub async fn install_bepinex<R: Runtime>(
     app: AppHandle<R>,
-    url: String,
-    destination: String,
-    cache_path: Option<String>,
+    request: BepInExInstallRequest,
+    retry: RetryPolicy,
+    job_id: Option<String>,
+    cancel: &CancellationToken,
 ) -> AppResult<()> {
+    let BepInExInstallRequest {
+        url,
+        destination,
+        cache_path,
+        extraction_policy: policy,
+    } = request;
     info!("install_bepinex: {} -> {}", url, destination);
+    let events = ProgressEmitter {
+        app: &app,
+        job_id: job_id.as_deref(),
+    };
     let dest = Path::new(&destination);
 
+    if dest.join("BepInEx").join("core").is_dir() && dest.join("metadata.json").is_file() {
+        snapshot_service::snapshot_before(&app, dest, SnapshotReason::BeforeBepinexReinstall);
+    }
+
     if let Some(ref cache) = cache_path {
         let cache_file = Path::new(cache);
         if cache_file.exists() {
             info!("Using cached BepInEx");
-            emit(&app, "extracting", 0.0, "Using cached BepInEx...");
-            extract_zip(cache_file, dest, |cur, total| {
-                emit(
-                    &app,
+            events.emit("extracting", 0.0, "Using cached BepInEx...");
+            extract_zip(cache_file, dest, &policy, cancel, |cur, total| {
+                events.emit(
                     "extracting",
                     cur as f64 / total as f64 * 100.0,
                     &format!("Extracting {}/{}", cur, total),
                 );
             })?;
-            emit(&app, "complete", 100.0, "Complete!");
+            events.emit("complete", 100.0, "Complete!");
             return Ok(());
         }
     }
 
     let temp = dest.with_extension("zip.tmp");
-    emit(&app, "downloading", 0.0, "Downloading...");
-    download_file(&url, &temp, |dl, total| {
-        if let Some(t) = total {
-            emit(
-                &app,
-                "downloading",
-                dl as f64 / t as f64 * 100.0,
-                &format!("Downloading... {:.0}%", dl as f64 / t as f64 * 100.0),
-            );
-        }
-    })
+    let staged = StagedArchive(&temp);
+    events.emit("downloading", 0.0, "Downloading...");
+    download_file(
+        &url,
+        &temp,
+        &retry,
+        cancel,
+        |dl, total| {
+            if let Some(t) = total {
+                events.emit(
+                    "downloading",
+                    dl as f64 / t as f64 * 100.0,
+                    &format!("Downloading... {:.0}%", dl as f64 / t as f64 * 100.0),
+                );
+            }
+        },
+        |notice| events.emit_retry(&notice),
+    )
     .await?;
 
     if let Some(ref cache) = cache_path {
@@ -76,18 +143,17 @@ pub async fn install_bepinex<R: Runtime>(
         }
     }
 
-    emit(&app, "extracting", 0.0, "Extracting...");
-    extract_zip(&temp, dest, |cur, total| {
-        emit(
-            &app,
+    events.emit("extracting", 0.0, "Extracting...");
+    extract_zip(&temp, dest, &policy, cancel, |cur, total| {
+        events.emit(
             "extracting",
             cur as f64 / total as f64 * 100.0,
             &format!("Extracting {}/{}", cur, total),
         );
     })?;
 
-    fs::remove_file(&temp).ok();
-    emit(&app, "complete", 100.0, "Complete!");
+    drop(staged);
+    events.emit("complete", 100.0, "Complete!");
     Ok(())
 }
 
@@ -95,23 +161,34 @@ pub async fn download_bepinex_to_cache<R: Runtime>(
     app: AppHandle<R>,
     url: String,
     cache_path: String,
+    retry: RetryPolicy,
 ) -> AppResult<()> {
     let cache_file = Path::new(&cache_path);
+    let events = ProgressEmitter {
+        app: &app,
+        job_id: None,
+    };
 
-    emit(&app, "downloading", 0.0, "Downloading...");
-    download_file(&url, cache_file, |dl, total| {
-        if let Some(t) = total {
-            emit(
-                &app,
-                "downloading",
-                dl as f64 / t as f64 * 100.0,
-                &format!("Downloading... {:.0}%", dl as f64 / t as f64 * 100.0),
-            );
-        }
-    })
+    events.emit("downloading", 0.0, "Downloading...");
+    download_file(
+        &url,
+        cache_file,
+        &retry,
+        &CancellationToken::default(),
+        |dl, total| {
+            if let Some(t) = total {
+                events.emit(
+                    "downloading",
+                    dl as f64 / t as f64 * 100.0,
+                    &format!("Downloading... {:.0}%", dl as f64 / t as f64 * 100.0),
+                );
+            }
+        },
+        |notice| events.emit_retry(&notice),
+    )
     .await?;
 
-    emit(&app, "complete", 100.0, "Complete!");
+    events.emit("complete", 100.0, "Complete!");
     Ok(())
 }
 
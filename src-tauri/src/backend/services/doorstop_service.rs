@@ -0,0 +1,134 @@
+use crate::backend::error::{AppError, AppResult};
+use std::fs;
+use std::path::Path;
+
+pub const PROXY_DLL_NAME: &str = "winhttp.dll";
+pub const CONFIG_NAME: &str = "doorstop_config.ini";
+/// First line of configs written by [`stage_proxy`], so a doorstop the user
+/// installed into the game directory themselves is never removed.
+const STAGED_MARKER: &str = "# Staged by Starlight";
+
+/// Copies doorstop's `winhttp.dll` proxy and its config from a profile into
+/// the game directory, for launches where the loader only looks there. The
+/// config is pointed at `target_assembly` and `coreclr_path`, given as the game
+/// process will see them.
+pub fn stage_proxy(
+    game_dir: &Path,
+    profile_dir: &Path,
+    target_assembly: &str,
+    coreclr_path: &str,
+) -> AppResult<()> {
+    let src_dll = profile_dir.join(PROXY_DLL_NAME);
+    let src_ini = profile_dir.join(CONFIG_NAME);
+
+    let dst_dll = game_dir.join(PROXY_DLL_NAME);
+    let dst_ini = game_dir.join(CONFIG_NAME);
+
+    if !src_dll.exists() {
+        return Err(AppError::validation(
+            "winhttp.dll not found in profile. Please wait for BepInEx installation to complete.",
+        ));
+    }
+    if !src_ini.exists() {
+        return Err(AppError::validation(
+            "doorstop_config.ini not found in profile. Please wait for BepInEx installation to complete.",
+        ));
+    }
+
+    fs::copy(&src_dll, &dst_dll)?;
+
+    let cleanup_dll = || {
+        if dst_dll.exists() {
+            let _ = fs::remove_file(&dst_dll);
+        }
+    };
+
+    let ini_content = fs::read_to_string(&src_ini).map_err(|e| {
+        cleanup_dll();
+        AppError::process(format!("Failed to read doorstop_config.ini: {e}"))
+    })?;
+
+    let target_assembly_str = target_assembly.replace('\\', "\\\\");
+    let coreclr_path_str = coreclr_path.replace('\\', "\\\\");
+
+    let mut modified_content = format!("{STAGED_MARKER}\n");
+    for line in ini_content.lines() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with('#')
+            && !trimmed.starts_with(';')
+            && trimmed.starts_with("target_assembly")
+            && trimmed.contains('=')
+        {
+            modified_content.push_str(&format!("target_assembly = \"{}\"\n", target_assembly_str));
+        } else if !trimmed.starts_with('#')
+            && !trimmed.starts_with(';')
+            && trimmed.starts_with("coreclr_path")
+            && trimmed.contains('=')
+        {
+            modified_content.push_str(&format!("coreclr_path = \"{}\"\n", coreclr_path_str));
+        } else {
+            modified_content.push_str(line);
+            modified_content.push('\n');
+        }
+    }
+
+    fs::write(&dst_ini, modified_content).map_err(|e| {
+        cleanup_dll();
+        AppError::process(format!("Failed to write doorstop_config.ini: {e}"))
+    })?;
+
+    Ok(())
+}
+
+/// Removes a proxy staged by [`stage_proxy`].
+pub fn remove_proxy(game_dir: &Path) -> AppResult<()> {
+    let dll_path = game_dir.join(PROXY_DLL_NAME);
+    let ini_path = game_dir.join(CONFIG_NAME);
+
+    if dll_path.exists() {
+        fs::remove_file(&dll_path)?;
+    }
+    if ini_path.exists() {
+        fs::remove_file(&ini_path)?;
+    }
+    Ok(())
+}
+
+/// Removes the proxy only when it was staged by [`stage_proxy`], so a vanilla
+/// launch from the game directory does not load a profile's mods.
+pub fn remove_staged_proxy(game_dir: &Path) -> AppResult<()> {
+    let staged = fs::read_to_string(game_dir.join(CONFIG_NAME))
+        .is_ok_and(|content| content.lines().next() == Some(STAGED_MARKER));
+    if staged {
+        remove_proxy(game_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_staged_proxies_are_removed() {
+        let temp = tempfile::tempdir().unwrap();
+        let profile = temp.path().join("profile");
+        let game_dir = temp.path().join("game");
+        fs::create_dir_all(&profile).unwrap();
+        fs::create_dir_all(&game_dir).unwrap();
+        fs::write(profile.join(PROXY_DLL_NAME), "proxy").unwrap();
+        fs::write(profile.join(CONFIG_NAME), "target_assembly = old\n").unwrap();
+
+        fs::write(game_dir.join(PROXY_DLL_NAME), "manual").unwrap();
+        fs::write(game_dir.join(CONFIG_NAME), "target_assembly = manual\n").unwrap();
+        remove_staged_proxy(&game_dir).unwrap();
+        assert!(game_dir.join(PROXY_DLL_NAME).exists());
+
+        stage_proxy(&game_dir, &profile, "C:\\BepInEx.dll", "C:\\coreclr.dll").unwrap();
+        let config = fs::read_to_string(game_dir.join(CONFIG_NAME)).unwrap();
+        assert!(config.contains("target_assembly = \"C:\\\\BepInEx.dll\""));
+        remove_staged_proxy(&game_dir).unwrap();
+        assert!(!game_dir.join(PROXY_DLL_NAME).exists());
+        assert!(!game_dir.join(CONFIG_NAME).exists());
+    }
+}
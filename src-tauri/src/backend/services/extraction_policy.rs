@@ -0,0 +1,298 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::http_download::sibling_path;
+use log::warn;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::read::ZipFile;
+
+const DEFAULT_MAX_ENTRIES: usize = 20_000;
+const DEFAULT_MAX_TOTAL_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+const DEFAULT_MAX_COMPRESSION_RATIO: u64 = 100;
+/// Entries smaller than this are exempt from the ratio check; tiny, highly
+/// repetitive files (configs, empty DLL sections) compress far beyond 100:1.
+const RATIO_CHECK_THRESHOLD: u64 = 1024 * 1024;
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+const BACKUP_SUFFIX: &str = "extract-backup";
+
+/// Resource limits applied to every archive we extract. Sizes are enforced on
+/// the bytes actually decompressed, not on the sizes declared in zip headers.
+/// Commands accept one from the frontend; omitted fields keep their defaults.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtractionPolicy {
+    pub max_entries: usize,
+    pub max_total_bytes: u64,
+    pub max_compression_ratio: u64,
+}
+
+impl Default for ExtractionPolicy {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+            max_compression_ratio: DEFAULT_MAX_COMPRESSION_RATIO,
+        }
+    }
+}
+
+/// Tracks one extraction against an [`ExtractionPolicy`]. Unless [`commit`] is
+/// called, dropping the session removes everything it created and puts back
+/// the files it overwrote.
+///
+/// Existing files are moved aside rather than truncated, so an overwrite never
+/// writes through to another hard link of the same file.
+///
+/// [`commit`]: ExtractionSession::commit
+pub struct ExtractionSession<'a> {
+    policy: &'a ExtractionPolicy,
+    total_bytes: u64,
+    created: Vec<PathBuf>,
+    /// Overwritten files as `(original, backup)` pairs.
+    replaced: Vec<(PathBuf, PathBuf)>,
+    written: HashSet<PathBuf>,
+    committed: bool,
+}
+
+impl<'a> ExtractionSession<'a> {
+    pub fn new(policy: &'a ExtractionPolicy, entry_count: usize) -> AppResult<Self> {
+        if entry_count > policy.max_entries {
+            return Err(AppError::validation(format!(
+                "Archive has {} entries, more than the limit of {}",
+                entry_count, policy.max_entries
+            )));
+        }
+
+        Ok(Self {
+            policy,
+            total_bytes: 0,
+            created: Vec::new(),
+            replaced: Vec::new(),
+            written: HashSet::new(),
+            committed: false,
+        })
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    pub fn create_dir_all(&mut self, path: &Path) -> AppResult<()> {
+        let topmost_missing = path
+            .ancestors()
+            .take_while(|ancestor| !ancestor.exists())
+            .last()
+            .map(Path::to_path_buf);
+        fs::create_dir_all(path)?;
+        if let Some(dir) = topmost_missing {
+            self.created.push(dir);
+        }
+        Ok(())
+    }
+
+    /// Streams a file entry to `out_path`, enforcing the byte and ratio limits
+    /// as data is decompressed. Symlink entries are refused.
+    pub fn extract_file(&mut self, entry: &mut ZipFile, out_path: &Path) -> AppResult<u64> {
+        self.extract_file_with(entry, out_path, |_| Ok(()))
+    }
+
+    /// Like [`extract_file`](Self::extract_file), calling `on_chunk` with the
+    /// size of every decompressed chunk. An error from `on_chunk` aborts.
+    pub fn extract_file_with<F>(
+        &mut self,
+        entry: &mut ZipFile,
+        out_path: &Path,
+        mut on_chunk: F,
+    ) -> AppResult<u64>
+    where
+        F: FnMut(u64) -> AppResult<()>,
+    {
+        if entry.is_symlink() {
+            return Err(AppError::validation(format!(
+                "Archive entry {} is a symbolic link",
+                entry.name()
+            )));
+        }
+
+        if let Some(parent) = out_path.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        if self.written.insert(out_path.to_path_buf()) {
+            if out_path.is_file() {
+                let backup = sibling_path(out_path, BACKUP_SUFFIX);
+                fs::rename(out_path, &backup)?;
+                self.replaced.push((out_path.to_path_buf(), backup));
+            } else {
+                self.created.push(out_path.to_path_buf());
+            }
+        }
+        let mut output = File::create(out_path)?;
+
+        let name = entry.name().to_string();
+        let compressed_size = entry.compressed_size().max(1);
+        let mut written = 0u64;
+        let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+        loop {
+            let read = match entry.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            written += read as u64;
+            self.total_bytes += read as u64;
+
+            if self.total_bytes > self.policy.max_total_bytes {
+                return Err(AppError::validation(format!(
+                    "Archive expands beyond the limit of {} bytes",
+                    self.policy.max_total_bytes
+                )));
+            }
+            if written > RATIO_CHECK_THRESHOLD
+                && written / compressed_size > self.policy.max_compression_ratio
+            {
+                return Err(AppError::validation(format!(
+                    "Archive entry {} exceeds the maximum compression ratio of {}:1",
+                    name, self.policy.max_compression_ratio
+                )));
+            }
+
+            output.write_all(&buffer[..read])?;
+            on_chunk(read as u64)?;
+        }
+
+        Ok(written)
+    }
+
+    pub fn commit(mut self) {
+        self.committed = true;
+        for (_, backup) in &self.replaced {
+            if let Err(e) = fs::remove_file(backup) {
+                warn!("Failed to remove extraction backup {:?}: {}", backup, e);
+            }
+        }
+    }
+}
+
+impl Drop for ExtractionSession<'_> {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+
+        for path in self.created.iter().rev() {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            if let Err(e) = result
+                && e.kind() != io::ErrorKind::NotFound
+            {
+                warn!("Failed to clean up partial extraction {:?}: {}", path, e);
+            }
+        }
+
+        for (original, backup) in self.replaced.iter().rev() {
+            if let Err(e) = fs::rename(backup, original) {
+                warn!("Failed to restore {:?} after extraction: {}", original, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn archive_with(name: &str, bytes: &[u8]) -> zip::ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(bytes).unwrap();
+        zip::ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn entry_count_is_limited() {
+        let policy = ExtractionPolicy {
+            max_entries: 1,
+            ..ExtractionPolicy::default()
+        };
+        assert!(ExtractionSession::new(&policy, 2).is_err());
+    }
+
+    #[test]
+    fn highly_compressed_entries_are_refused_and_cleaned_up() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("out");
+        let mut archive = archive_with("nested/bomb.bin", &vec![0u8; 4 * 1024 * 1024]);
+        let policy = ExtractionPolicy::default();
+
+        {
+            let mut session = ExtractionSession::new(&policy, archive.len()).unwrap();
+            let mut entry = archive.by_index(0).unwrap();
+            let out_path = root.join("nested").join("bomb.bin");
+            assert!(session.extract_file(&mut entry, &out_path).is_err());
+        }
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn total_bytes_are_limited() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("out");
+        let mut archive = archive_with("big.txt", b"0123456789");
+        let policy = ExtractionPolicy {
+            max_total_bytes: 5,
+            ..ExtractionPolicy::default()
+        };
+
+        let mut session = ExtractionSession::new(&policy, archive.len()).unwrap();
+        let mut entry = archive.by_index(0).unwrap();
+        assert!(
+            session
+                .extract_file(&mut entry, &root.join("big.txt"))
+                .is_err()
+        );
+        drop(entry);
+        drop(session);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn overwritten_files_are_restored_unless_committed() {
+        let temp = tempfile::tempdir().unwrap();
+        let target = temp.path().join("mod.dll");
+        let link = temp.path().join("linked.dll");
+        fs::write(&target, b"original").unwrap();
+        fs::hard_link(&target, &link).unwrap();
+        let policy = ExtractionPolicy::default();
+
+        {
+            let mut archive = archive_with("mod.dll", b"replacement");
+            let mut session = ExtractionSession::new(&policy, archive.len()).unwrap();
+            session
+                .extract_file(&mut archive.by_index(0).unwrap(), &target)
+                .unwrap();
+            assert_eq!(fs::read(&target).unwrap(), b"replacement");
+        }
+        assert_eq!(fs::read(&target).unwrap(), b"original");
+
+        let mut archive = archive_with("mod.dll", b"replacement");
+        let mut session = ExtractionSession::new(&policy, archive.len()).unwrap();
+        session
+            .extract_file(&mut archive.by_index(0).unwrap(), &target)
+            .unwrap();
+        session.commit();
+        assert_eq!(fs::read(&target).unwrap(), b"replacement");
+        assert_eq!(fs::read(&link).unwrap(), b"original");
+        assert!(!sibling_path(&target, BACKUP_SUFFIX).exists());
+    }
+}
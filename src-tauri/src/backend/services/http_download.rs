@@ -1,19 +1,71 @@
 use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::extraction_policy::{ExtractionPolicy, ExtractionSession};
+use crate::backend::services::http_retry::{
+    RetryNotice, RetryPolicy, retry_transient, status_error,
+};
+use crate::backend::state::archive_operations::CancellationToken;
 use futures_util::StreamExt;
-use log::debug;
-use reqwest::Client;
-use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::path::Path;
+use log::{debug, info};
+use reqwest::header::{CONTENT_RANGE, ETAG, HeaderMap, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::{Client, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use zip::ZipArchive;
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+const PART_SUFFIX: &str = "part";
+const PART_STATE_SUFFIX: &str = "part.json";
 
-pub async fn download_file<F>(url: &str, dest_path: &Path, mut on_progress: F) -> AppResult<()>
+/// Validators saved next to a `.part` file so a later attempt can resume it.
+#[derive(Serialize, Deserialize)]
+struct PartialDownloadState {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl PartialDownloadState {
+    fn from_response(url: &str, headers: &HeaderMap) -> Self {
+        let header_value = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Self {
+            url: url.to_string(),
+            // Weak validators are not allowed in If-Range.
+            etag: header_value(ETAG).filter(|etag| !etag.starts_with("W/")),
+            last_modified: header_value(LAST_MODIFIED),
+        }
+    }
+
+    fn validator(&self) -> Option<&str> {
+        self.etag.as_deref().or(self.last_modified.as_deref())
+    }
+}
+
+struct ResumePoint {
+    offset: u64,
+    validator: String,
+}
+
+pub async fn download_file<F, R>(
+    url: &str,
+    dest_path: &Path,
+    retry: &RetryPolicy,
+    cancel: &CancellationToken,
+    on_progress: F,
+    on_retry: R,
+) -> AppResult<()>
 where
-    F: FnMut(u64, Option<u64>),
+    F: Fn(u64, Option<u64>),
+    R: FnMut(RetryNotice),
 {
     if let Some(parent) = dest_path.parent() {
         fs::create_dir_all(parent)?;
@@ -24,30 +76,166 @@ where
         .timeout(REQUEST_TIMEOUT)
         .build()?;
 
-    let response = client.get(url).send().await?;
+    retry_transient(
+        retry,
+        || download_attempt(&client, url, dest_path, cancel, &on_progress),
+        on_retry,
+    )
+    .await
+}
+
+async fn download_attempt<F>(
+    client: &Client,
+    url: &str,
+    dest_path: &Path,
+    cancel: &CancellationToken,
+    on_progress: &F,
+) -> AppResult<()>
+where
+    F: Fn(u64, Option<u64>),
+{
+    cancel.check()?;
+    let part_path = sibling_path(dest_path, PART_SUFFIX);
+    let state_path = sibling_path(dest_path, PART_STATE_SUFFIX);
+
+    let mut resume = load_resume_point(url, &part_path, &state_path);
+    let mut response = send_download_request(client, url, resume.as_ref()).await?;
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE && resume.is_some() {
+        debug!("Saved partial download is no longer valid, restarting");
+        discard_partial(&part_path, &state_path);
+        resume = None;
+        response = send_download_request(client, url, None).await?;
+    }
+
     if !response.status().is_success() {
-        return Err(AppError::other(format!(
-            "Download failed: HTTP {}",
-            response.status()
-        )));
+        return Err(status_error(response.status(), response.headers()));
     }
 
-    let total = response.content_length();
-    let mut downloaded = 0u64;
-    let mut file = File::create(dest_path)?;
-    let mut stream = response.bytes_stream();
+    let resumed_offset = match &resume {
+        Some(point) if response.status() == StatusCode::PARTIAL_CONTENT => {
+            let range = content_range(response.headers());
+            if range.map(|(start, _)| start) != Some(point.offset) {
+                discard_partial(&part_path, &state_path);
+                return Err(AppError::other(
+                    "Download failed: server returned an unexpected byte range",
+                ));
+            }
+            Some(point.offset)
+        }
+        Some(_) => {
+            debug!("Server ignored range request, restarting download from zero");
+            None
+        }
+        None => None,
+    };
+
+    let state = PartialDownloadState::from_response(url, response.headers());
+    let total = match resumed_offset {
+        Some(offset) => content_range(response.headers())
+            .and_then(|(_, total)| total)
+            .or_else(|| response.content_length().map(|len| offset + len)),
+        None => response.content_length(),
+    };
+
+    let mut file = match resumed_offset {
+        Some(_) => OpenOptions::new().append(true).open(&part_path)?,
+        None => File::create(&part_path)?,
+    };
+    if state.validator().is_some() {
+        fs::write(&state_path, serde_json::to_vec(&state)?)?;
+    } else {
+        fs::remove_file(&state_path).ok();
+    }
+
+    let mut downloaded = resumed_offset.unwrap_or(0);
+    if downloaded > 0 {
+        info!("Resuming download of {} at byte {}", url, downloaded);
+        on_progress(downloaded, total);
+    }
 
+    let mut stream = response.bytes_stream();
     while let Some(chunk) = stream.next().await {
+        if cancel.is_cancelled() {
+            drop(file);
+            discard_partial(&part_path, &state_path);
+            return cancel.check();
+        }
         let chunk = chunk?;
         file.write_all(&chunk)?;
         downloaded += chunk.len() as u64;
         on_progress(downloaded, total);
     }
 
+    file.flush()?;
+    drop(file);
+    fs::rename(&part_path, dest_path)?;
+    fs::remove_file(&state_path).ok();
+
     Ok(())
 }
 
-pub fn extract_zip<F>(zip_path: &Path, dest_path: &Path, mut on_progress: F) -> AppResult<()>
+async fn send_download_request(
+    client: &Client,
+    url: &str,
+    resume: Option<&ResumePoint>,
+) -> AppResult<Response> {
+    let mut request = client.get(url);
+    if let Some(point) = resume {
+        request = request
+            .header(RANGE, format!("bytes={}-", point.offset))
+            .header(IF_RANGE, &point.validator);
+    }
+    Ok(request.send().await?)
+}
+
+fn load_resume_point(url: &str, part_path: &Path, state_path: &Path) -> Option<ResumePoint> {
+    let offset = fs::metadata(part_path).ok()?.len();
+    let state = fs::read(state_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<PartialDownloadState>(&bytes).ok());
+
+    let resume = state
+        .filter(|state| state.url == url && offset > 0)
+        .and_then(|state| {
+            state.validator().map(|validator| ResumePoint {
+                offset,
+                validator: validator.to_string(),
+            })
+        });
+
+    if resume.is_none() {
+        discard_partial(part_path, state_path);
+    }
+    resume
+}
+
+fn discard_partial(part_path: &Path, state_path: &Path) {
+    fs::remove_file(part_path).ok();
+    fs::remove_file(state_path).ok();
+}
+
+pub fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Parses `Content-Range: bytes <start>-<end>/<total>` into its start and total.
+fn content_range(headers: &HeaderMap) -> Option<(u64, Option<u64>)> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+    Some((start.trim().parse().ok()?, total.trim().parse().ok()))
+}
+
+pub fn extract_zip<F>(
+    zip_path: &Path,
+    dest_path: &Path,
+    policy: &ExtractionPolicy,
+    cancel: &CancellationToken,
+    mut on_progress: F,
+) -> AppResult<()>
 where
     F: FnMut(usize, usize),
 {
@@ -59,7 +247,10 @@ where
         return Ok(());
     }
 
+    let mut session = ExtractionSession::new(policy, total_entries)?;
     for i in 0..total_entries {
+        // Bailing out here drops the session, which removes what was extracted.
+        cancel.check()?;
         let mut entry = archive.by_index(i)?;
         let Some(entry_path) = entry.enclosed_name().map(|p| p.to_path_buf()) else {
             continue;
@@ -67,25 +258,60 @@ where
 
         let output_path = dest_path.join(entry_path);
         if entry.is_dir() {
-            fs::create_dir_all(&output_path)?;
+            session.create_dir_all(&output_path)?;
         } else {
-            if let Some(parent) = output_path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-            let mut output = File::create(&output_path)?;
-            let mut buffer = Vec::with_capacity(entry.size() as usize);
-            entry.read_to_end(&mut buffer)?;
-            output.write_all(&buffer)?;
+            session.extract_file(&mut entry, &output_path)?;
         }
 
         on_progress(i + 1, total_entries);
     }
 
     debug!(
-        "Extracted zip archive with {} entries from {}",
+        "Extracted zip archive with {} entries ({} bytes) from {}",
         total_entries,
+        session.total_bytes(),
         zip_path.display()
     );
+    session.commit();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn content_range_parses_start_and_total() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_RANGE,
+            HeaderValue::from_static("bytes 100-999/1000"),
+        );
+        assert_eq!(content_range(&headers), Some((100, Some(1000))));
+
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 100-999/*"));
+        assert_eq!(content_range(&headers), Some((100, None)));
+    }
+
+    #[test]
+    fn partial_state_skips_weak_etag() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("W/\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        let state = PartialDownloadState::from_response("https://example.com", &headers);
+        assert_eq!(state.validator(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+    }
+
+    #[test]
+    fn sibling_path_appends_suffix() {
+        assert_eq!(
+            sibling_path(Path::new("/tmp/BepInEx.zip"), PART_SUFFIX),
+            PathBuf::from("/tmp/BepInEx.zip.part")
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::backend::error::AppError;
+use log::warn;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::time::{Duration, SystemTime};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: BASE_DELAY,
+            max_delay: MAX_DELAY,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RetryNotice {
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn with_max_attempts(max_attempts: Option<u32>) -> Self {
+        let mut policy = Self::default();
+        if let Some(max_attempts) = max_attempts {
+            policy.max_attempts = max_attempts.max(1);
+        }
+        policy
+    }
+
+    /// Returns how long to wait before the next attempt, or `None` when the
+    /// error is not transient or the attempt budget is spent.
+    fn next_delay(&self, attempt: u32, error: &AppError) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        match error {
+            AppError::HttpStatus(status, retry_after) if is_retryable_status(*status) => {
+                Some(retry_after.map_or_else(
+                    || self.backoff_delay(attempt, jitter_fraction()),
+                    |delay| delay.min(self.max_delay),
+                ))
+            }
+            AppError::Http(e) if is_transient_http_error(e) => {
+                Some(self.backoff_delay(attempt, jitter_fraction()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Exponential backoff capped at `max_delay`, jittered into `[delay / 2, delay]`.
+    fn backoff_delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        delay.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// Runs `operation` until it succeeds, fails with a non-transient error, or
+/// runs out of attempts. `on_retry` is called before each backoff sleep.
+pub async fn retry_transient<T, Op, Fut, OnRetry>(
+    policy: &RetryPolicy,
+    mut operation: Op,
+    mut on_retry: OnRetry,
+) -> Result<T, AppError>
+where
+    Op: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+    OnRetry: FnMut(RetryNotice),
+{
+    let mut attempt = 1;
+    loop {
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        let Some(delay) = policy.next_delay(attempt, &error) else {
+            return Err(error);
+        };
+
+        warn!(
+            "Download attempt {}/{} failed, retrying in {:?}: {}",
+            attempt, policy.max_attempts, delay, error
+        );
+        on_retry(RetryNotice {
+            attempt,
+            max_attempts: policy.max_attempts,
+            delay,
+        });
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+pub fn status_error(status: StatusCode, headers: &HeaderMap) -> AppError {
+    AppError::http_status(status, parse_retry_after(headers))
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
+fn is_transient_http_error(error: &reqwest::Error) -> bool {
+    if let Some(status) = error.status() {
+        return is_retryable_status(status);
+    }
+    error.is_timeout() || error.is_connect() || error.is_request() || error.is_body()
+}
+
+/// Only the delay-seconds form of `Retry-After` is honoured; HTTP dates fall
+/// back to the regular backoff.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+fn jitter_fraction() -> f64 {
+    let seed = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let bits = RandomState::new().hash_one(seed) >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff_delay(1, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.backoff_delay(3, 1.0), Duration::from_secs(4));
+        assert_eq!(policy.backoff_delay(10, 1.0), MAX_DELAY);
+        assert_eq!(policy.backoff_delay(2, 0.0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn checksum_failures_are_never_retried() {
+        let policy = RetryPolicy::default();
+        let error = AppError::validation("Checksum mismatch: expected a, got b");
+        assert_eq!(policy.next_delay(1, &error), None);
+    }
+
+    #[test]
+    fn server_errors_honour_retry_after() {
+        let policy = RetryPolicy::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+
+        let error = status_error(StatusCode::SERVICE_UNAVAILABLE, &headers);
+        assert_eq!(policy.next_delay(1, &error), Some(Duration::from_secs(7)));
+
+        let error = status_error(StatusCode::NOT_FOUND, &headers);
+        assert_eq!(policy.next_delay(1, &error), None);
+    }
+
+    #[test]
+    fn attempts_are_bounded() {
+        let policy = RetryPolicy::with_max_attempts(Some(2));
+        let error = AppError::http_status(StatusCode::BAD_GATEWAY, None);
+        assert!(policy.next_delay(1, &error).is_some());
+        assert_eq!(policy.next_delay(2, &error), None);
+    }
+}
@@ -1,22 +1,47 @@
 use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::doorstop_service;
 use crate::backend::services::epic_auth_service::{EpicAuthService, load_session};
-use crate::backend::state::game_runtime;
+use crate::backend::services::process_scan_service::{self, GAME_PROCESS_NAME};
+#[cfg(target_os = "linux")]
+use crate::backend::services::wine_service::{self, CompatRuntime};
+use crate::backend::state::game_runtime::{self, GameLaunch, GameLaunchKind};
 use log::{debug, info, warn};
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Command;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Runtime};
 
+const STEAM_APP_ID: &str = "945360";
+const STEAM_PROCESS_TIMEOUT: Duration = Duration::from_secs(90);
+const STEAM_PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchMode {
+    /// Start the game executable ourselves.
+    #[default]
+    Direct,
+    /// Ask the Steam client to start the game, keeping the overlay and DRM
+    /// handshake. Only valid for the Steam build.
+    Steam,
+}
+
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LaunchModdedArgs {
     pub game_exe: String,
     pub profile_id: String,
-    #[cfg(windows)]
+    #[cfg(any(windows, target_os = "linux"))]
     pub profile_path: String,
     pub bepinex_dll: String,
     pub dotnet_dir: String,
     pub coreclr_path: String,
     pub platform: String,
+    #[serde(default)]
+    pub launch_mode: LaunchMode,
+    #[cfg(target_os = "linux")]
+    #[serde(default)]
+    pub compat_runtime: CompatRuntime,
 }
 
 #[derive(serde::Deserialize)]
@@ -24,6 +49,11 @@ pub struct LaunchModdedArgs {
 pub struct LaunchVanillaArgs {
     pub game_exe: String,
     pub platform: String,
+    #[serde(default)]
+    pub launch_mode: LaunchMode,
+    #[cfg(target_os = "linux")]
+    #[serde(default)]
+    pub compat_runtime: CompatRuntime,
 }
 
 #[cfg(windows)]
@@ -58,44 +88,301 @@ async fn attach_epic_launch_token(cmd: &mut Command, platform: &str) -> AppResul
     Ok(())
 }
 
+/// Runs the Windows build through Wine or Proton, with a prefix per profile.
+#[cfg(target_os = "linux")]
+fn game_command<R: Runtime>(
+    app: &AppHandle<R>,
+    game_exe: &str,
+    runtime: &CompatRuntime,
+    profile_id: Option<&str>,
+) -> AppResult<Command> {
+    let prefix = wine_service::prefix_dir(&wine_service::compat_dir(app)?, profile_id)?;
+    wine_service::build_command(runtime, &prefix, game_exe, profile_id.is_some())
+}
+
+/// Wine only looks for the doorstop proxy in the game directory, and a game
+/// started by the Steam client does not inherit our DLL directory, so in those
+/// cases it is staged there from the profile.
+#[cfg(any(windows, target_os = "linux"))]
+fn stage_doorstop(args: &LaunchModdedArgs) -> AppResult<()> {
+    doorstop_service::stage_proxy(
+        game_dir(&args.game_exe)?,
+        Path::new(&args.profile_path),
+        &game_path(&args.bepinex_dll),
+        &game_path(&args.coreclr_path),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn game_command<R: Runtime>(
+    _app: &AppHandle<R>,
+    game_exe: &str,
+    _profile_id: Option<&str>,
+) -> AppResult<Command> {
+    let mut cmd = Command::new(game_exe);
+    cmd.current_dir(game_dir(game_exe)?);
+    Ok(cmd)
+}
+
+fn game_dir(game_exe: &str) -> AppResult<&Path> {
+    Path::new(game_exe)
+        .parent()
+        .ok_or_else(|| AppError::validation("Invalid game path"))
+}
+
+/// Paths handed to doorstop are resolved inside the game process, which sees
+/// Windows paths when running under Wine.
+fn game_path(path: &str) -> String {
+    #[cfg(target_os = "linux")]
+    {
+        wine_service::to_wine_path(path)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        path.to_string()
+    }
+}
+
+#[cfg(windows)]
+fn steam_command() -> AppResult<Command> {
+    use winreg::{RegKey, enums::HKEY_CURRENT_USER};
+
+    let steam_exe: String = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey("Software\\Valve\\Steam")
+        .and_then(|key| key.get_value("SteamExe"))
+        .map_err(|e| AppError::platform(format!("Steam installation not found: {e}")))?;
+    Ok(Command::new(steam_exe))
+}
+
+#[cfg(not(windows))]
+fn steam_command() -> AppResult<Command> {
+    Ok(Command::new("steam"))
+}
+
+/// Steam joins the launch options and splits them again on whitespace, so
+/// arguments such as paths with spaces have to be quoted.
+fn steam_launch_option(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) || arg.contains('"') {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Starts the game through `steam -applaunch`, passing `game_args` as launch
+/// options. The game is not our child, so it is found by scanning processes
+/// and tracked by PID once it appears.
+fn launch_through_steam<R: Runtime>(
+    app: AppHandle<R>,
+    game_args: Vec<String>,
+    launch: GameLaunch,
+) -> AppResult<()> {
+    if launch.platform != "steam" {
+        return Err(AppError::validation(
+            "Launching through Steam requires the Steam version of the game",
+        ));
+    }
+
+    let existing = process_scan_service::find_processes(GAME_PROCESS_NAME);
+    let mut cmd = steam_command()?;
+    cmd.args(["-applaunch", STEAM_APP_ID])
+        .args(game_args.iter().map(|arg| steam_launch_option(arg)));
+    let mut steam = cmd
+        .spawn()
+        .map_err(|e| AppError::process(format!("Failed to start Steam: {e}")))?;
+    info!("Requested Steam launch of app {}", STEAM_APP_ID);
+
+    // When the client was not running yet, this process is the client itself.
+    std::thread::spawn(move || {
+        if let Err(e) = steam.wait() {
+            warn!("Failed to reap Steam process: {}", e);
+        }
+    });
+
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + STEAM_PROCESS_TIMEOUT;
+        while Instant::now() < deadline {
+            std::thread::sleep(STEAM_PROCESS_POLL_INTERVAL);
+            let started = process_scan_service::find_processes(GAME_PROCESS_NAME)
+                .into_iter()
+                .find(|pid| !existing.contains(pid));
+            if let Some(pid) = started {
+                info!("Found game process {} started by Steam", pid);
+                if let Err(e) = game_runtime::register_external_process(app, pid, launch) {
+                    warn!("Failed to track game process {}: {}", pid, e);
+                }
+                return;
+            }
+        }
+        warn!(
+            "Game process did not appear within {:?} of the Steam launch",
+            STEAM_PROCESS_TIMEOUT
+        );
+    });
+
+    Ok(())
+}
+
 fn launch_process<R: Runtime>(
     app: AppHandle<R>,
     mut cmd: Command,
-    profile_id: Option<String>,
+    launch: GameLaunch,
 ) -> AppResult<()> {
+    // Under Wine or Proton the child is only a wrapper; leading a process
+    // group lets a stop request reach the game behind it.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
     let child = cmd
         .spawn()
         .map_err(|e| AppError::process(format!("Failed to launch game: {e}")))?;
-    game_runtime::register_launched_process(app, child, profile_id)
+    game_runtime::register_launched_process(app, child, launch)
 }
 
 pub async fn launch_modded<R: Runtime>(app: AppHandle<R>, args: LaunchModdedArgs) -> AppResult<()> {
     info!("game_launch_modded: game_exe={}", args.game_exe);
 
-    let game_dir = PathBuf::from(&args.game_exe)
-        .parent()
-        .ok_or_else(|| AppError::validation("Invalid game path"))?
-        .to_path_buf();
+    let doorstop_args = vec![
+        "--doorstop-enabled".to_string(),
+        "true".to_string(),
+        "--doorstop-target-assembly".to_string(),
+        game_path(&args.bepinex_dll),
+        "--doorstop-clr-corlib-dir".to_string(),
+        game_path(&args.dotnet_dir),
+        "--doorstop-clr-runtime-coreclr-path".to_string(),
+        game_path(&args.coreclr_path),
+    ];
+    let launch = GameLaunch {
+        kind: GameLaunchKind::Modded,
+        platform: args.platform.clone(),
+        profile_id: Some(args.profile_id.clone()),
+    };
+    if args.launch_mode == LaunchMode::Steam {
+        // Steam runs Proton with its own environment, so the DLL override
+        // doorstop needs under Wine cannot be passed along.
+        #[cfg(target_os = "linux")]
+        return Err(AppError::validation(
+            "Modded profiles cannot be launched through Steam on Linux; use a direct launch instead",
+        ));
+        #[cfg(windows)]
+        stage_doorstop(&args)?;
+        #[cfg(not(target_os = "linux"))]
+        return launch_through_steam(app, doorstop_args, launch);
+    }
 
     #[cfg(windows)]
     set_dll_directory(&args.profile_path)?;
+    #[cfg(target_os = "linux")]
+    stage_doorstop(&args)?;
 
-    let mut cmd = Command::new(&args.game_exe);
-    cmd.current_dir(game_dir)
-        .args(["--doorstop-enabled", "true"])
-        .args(["--doorstop-target-assembly", &args.bepinex_dll])
-        .args(["--doorstop-clr-corlib-dir", &args.dotnet_dir])
-        .args(["--doorstop-clr-runtime-coreclr-path", &args.coreclr_path]);
+    let mut cmd = game_command(
+        &app,
+        &args.game_exe,
+        #[cfg(target_os = "linux")]
+        &args.compat_runtime,
+        Some(&args.profile_id),
+    )?;
+    cmd.args(&doorstop_args);
 
     attach_epic_launch_token(&mut cmd, &args.platform).await?;
-    launch_process(app, cmd, Some(args.profile_id))
+    launch_process(app, cmd, launch)
 }
 
 pub async fn launch_vanilla<R: Runtime>(
     app: AppHandle<R>,
     args: LaunchVanillaArgs,
 ) -> AppResult<()> {
-    let mut cmd = Command::new(&args.game_exe);
+    let launch = GameLaunch {
+        kind: GameLaunchKind::Vanilla,
+        platform: args.platform.clone(),
+        profile_id: None,
+    };
+    doorstop_service::remove_staged_proxy(game_dir(&args.game_exe)?)?;
+    if args.launch_mode == LaunchMode::Steam {
+        return launch_through_steam(app, Vec::new(), launch);
+    }
+
+    let mut cmd = game_command(
+        &app,
+        &args.game_exe,
+        #[cfg(target_os = "linux")]
+        &args.compat_runtime,
+        None,
+    )?;
     attach_epic_launch_token(&mut cmd, &args.platform).await?;
-    launch_process(app, cmd, None)
+    launch_process(app, cmd, launch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_os = "linux")]
+    use std::fs;
+
+    #[test]
+    fn steam_launch_options_are_quoted() {
+        assert_eq!(
+            steam_launch_option("--doorstop-enabled"),
+            "--doorstop-enabled"
+        );
+        assert_eq!(
+            steam_launch_option("C:\\Program Files\\BepInEx.dll"),
+            "\"C:\\Program Files\\BepInEx.dll\""
+        );
+        assert_eq!(steam_launch_option("a\"b"), "\"a\\\"b\"");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn modded_wine_command_can_reach_the_proxy() {
+        let temp = tempfile::tempdir().unwrap();
+        let game_dir = temp.path().join("Among Us");
+        let profile = temp.path().join("profiles").join("p1");
+        fs::create_dir_all(&game_dir).unwrap();
+        fs::create_dir_all(&profile).unwrap();
+        fs::write(profile.join("winhttp.dll"), "proxy").unwrap();
+        fs::write(
+            profile.join("doorstop_config.ini"),
+            "[General]\nenabled = true\ntarget_assembly = old\n",
+        )
+        .unwrap();
+
+        let game_exe = game_dir.join("Among Us.exe").to_string_lossy().to_string();
+        let bepinex_dll = profile.join("BepInEx").join("core").join("BepInEx.dll");
+        let args = LaunchModdedArgs {
+            game_exe: game_exe.clone(),
+            profile_id: "p1".to_string(),
+            profile_path: profile.to_string_lossy().to_string(),
+            bepinex_dll: bepinex_dll.to_string_lossy().to_string(),
+            dotnet_dir: String::new(),
+            coreclr_path: String::new(),
+            platform: "steam".to_string(),
+            launch_mode: LaunchMode::Direct,
+            compat_runtime: CompatRuntime::default(),
+        };
+        stage_doorstop(&args).unwrap();
+
+        let prefix = wine_service::prefix_dir(&temp.path().join("compat"), Some("p1")).unwrap();
+        let cmd =
+            wine_service::build_command(&args.compat_runtime, &prefix, &game_exe, true).unwrap();
+        let working_dir = cmd.get_current_dir().unwrap();
+        assert_eq!(
+            fs::read_to_string(working_dir.join("winhttp.dll")).unwrap(),
+            "proxy"
+        );
+        let config = fs::read_to_string(working_dir.join("doorstop_config.ini")).unwrap();
+        assert!(config.contains(&format!(
+            "target_assembly = \"{}\"",
+            game_path(&args.bepinex_dll).replace('\\', "\\\\")
+        )));
+        assert!(cmd.get_envs().any(|(key, value)| {
+            key == "WINEDLLOVERRIDES" && value == Some(std::ffi::OsStr::new("winhttp=n,b"))
+        }));
+
+        let vanilla =
+            wine_service::build_command(&args.compat_runtime, &prefix, &game_exe, false).unwrap();
+        assert!(vanilla.get_envs().any(|(key, value)| {
+            key == "WINEDLLOVERRIDES" && value == Some(std::ffi::OsStr::new("winhttp=b"))
+        }));
+    }
 }
@@ -0,0 +1,255 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::http_download::sibling_path;
+use crate::backend::services::http_retry::RetryPolicy;
+use crate::backend::services::mod_dependency_service::ModDependency;
+use crate::backend::services::mod_download_service::{self, ModDownloadRequest};
+use crate::backend::state::archive_operations::CancellationToken;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+
+pub const LOCKFILE_NAME: &str = "starlight.lock";
+const LOCKFILE_VERSION: u32 = 1;
+
+/// Serialises read-modify-write cycles so parallel downloads into the same
+/// profile cannot drop each other's entries.
+static LOCKFILE_GUARD: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModLockContext {
+    pub profile_path: String,
+    pub version: String,
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<ModDependency>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LockedMod {
+    pub version: String,
+    /// Path of the installed file relative to the profile root, `/`-separated.
+    pub file: String,
+    pub url: String,
+    pub sha256: String,
+    pub size: u64,
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<ModDependency>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProfileLockfile {
+    pub lockfile_version: u32,
+    #[serde(default)]
+    pub mods: BTreeMap<String, LockedMod>,
+}
+
+impl Default for ProfileLockfile {
+    fn default() -> Self {
+        Self {
+            lockfile_version: LOCKFILE_VERSION,
+            mods: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn lockfile_path(profile_dir: &Path) -> PathBuf {
+    profile_dir.join(LOCKFILE_NAME)
+}
+
+pub fn read_lockfile(profile_dir: &Path) -> AppResult<ProfileLockfile> {
+    let path = lockfile_path(profile_dir);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(ProfileLockfile::default());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let lockfile: ProfileLockfile = serde_json::from_str(&content)?;
+    if lockfile.lockfile_version > LOCKFILE_VERSION {
+        return Err(AppError::validation(format!(
+            "{} was written by a newer version of Starlight (lockfile version {})",
+            path.display(),
+            lockfile.lockfile_version
+        )));
+    }
+    Ok(lockfile)
+}
+
+pub fn write_lockfile(profile_dir: &Path, lockfile: &ProfileLockfile) -> AppResult<()> {
+    let path = lockfile_path(profile_dir);
+    let staging = sibling_path(&path, "tmp");
+    fs::write(&staging, serde_json::to_string_pretty(lockfile)?)?;
+    fs::rename(&staging, &path)?;
+    Ok(())
+}
+
+/// Records a verified download in the profile's lockfile.
+pub fn record_mod(
+    mod_id: &str,
+    url: &str,
+    sha256: &str,
+    installed_path: &Path,
+    context: &ModLockContext,
+) -> AppResult<()> {
+    let profile_dir = Path::new(&context.profile_path);
+    let file = relative_lock_path(profile_dir, installed_path)?;
+    let size = fs::metadata(installed_path)?.len();
+
+    let _guard = LOCKFILE_GUARD
+        .lock()
+        .map_err(|_| AppError::state("Failed to acquire lockfile lock"))?;
+    let mut lockfile = read_lockfile(profile_dir)?;
+    lockfile.mods.insert(
+        mod_id.to_string(),
+        LockedMod {
+            version: context.version.clone(),
+            file,
+            url: url.to_string(),
+            sha256: sha256.to_lowercase(),
+            size,
+            platform: context.platform.clone(),
+            dependencies: context.dependencies.clone(),
+        },
+    );
+    write_lockfile(profile_dir, &lockfile)
+}
+
+/// Downloads a locked mod again from its recorded URL, verified against the
+/// recorded checksum.
+pub async fn reinstall_locked_mod<R: Runtime>(
+    app: &AppHandle<R>,
+    profile_dir: &Path,
+    mod_id: &str,
+    locked: &LockedMod,
+) -> AppResult<()> {
+    let destination = resolve_lock_path(profile_dir, &locked.file)?;
+    info!("Repairing {} from lockfile", mod_id);
+
+    mod_download_service::download_mod(
+        app.clone(),
+        ModDownloadRequest {
+            mod_id: mod_id.to_string(),
+            url: locked.url.clone(),
+            destination: destination.to_string_lossy().to_string(),
+            expected_checksum: locked.sha256.clone(),
+            lock: Some(ModLockContext {
+                profile_path: profile_dir.to_string_lossy().to_string(),
+                version: locked.version.clone(),
+                platform: locked.platform.clone(),
+                dependencies: locked.dependencies.clone(),
+            }),
+        },
+        RetryPolicy::default(),
+        None,
+        &CancellationToken::default(),
+    )
+    .await
+}
+
+pub fn remove_mods(profile_dir: &Path, mod_ids: &[String]) -> AppResult<()> {
+    if mod_ids.is_empty() {
+        return Ok(());
+    }
+
+    let _guard = LOCKFILE_GUARD
+        .lock()
+        .map_err(|_| AppError::state("Failed to acquire lockfile lock"))?;
+    let mut lockfile = read_lockfile(profile_dir)?;
+    for mod_id in mod_ids {
+        lockfile.mods.remove(mod_id);
+    }
+    info!(
+        "Removed {} stale lockfile entries from {}",
+        mod_ids.len(),
+        profile_dir.display()
+    );
+    write_lockfile(profile_dir, &lockfile)
+}
+
+fn relative_lock_path(profile_dir: &Path, installed_path: &Path) -> AppResult<String> {
+    let relative = installed_path.strip_prefix(profile_dir).map_err(|_| {
+        AppError::validation(format!(
+            "{} is not inside profile {}",
+            installed_path.display(),
+            profile_dir.display()
+        ))
+    })?;
+
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    Ok(parts.join("/"))
+}
+
+pub fn resolve_lock_path(profile_dir: &Path, file: &str) -> AppResult<PathBuf> {
+    let relative = Path::new(file);
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(AppError::validation(format!(
+            "Unsafe path in lockfile: {file}"
+        )));
+    }
+    Ok(profile_dir.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::services::mod_cache_service::sha256_file;
+
+    #[test]
+    fn lock_paths_are_relative_and_safe() {
+        let profile = Path::new("/data/profiles/p1");
+        let installed = profile.join("BepInEx").join("plugins").join("Mod.dll");
+        assert_eq!(
+            relative_lock_path(profile, &installed).unwrap(),
+            "BepInEx/plugins/Mod.dll"
+        );
+        assert!(relative_lock_path(profile, Path::new("/elsewhere/Mod.dll")).is_err());
+        assert!(resolve_lock_path(profile, "../../escape.dll").is_err());
+    }
+
+    #[test]
+    fn recorded_mods_can_be_removed() {
+        let temp = tempfile::tempdir().unwrap();
+        let profile = temp.path();
+        let plugins = profile.join("BepInEx").join("plugins");
+        fs::create_dir_all(&plugins).unwrap();
+
+        let context = ModLockContext {
+            profile_path: profile.to_string_lossy().to_string(),
+            version: "1.0.0".to_string(),
+            platform: None,
+            dependencies: Vec::new(),
+        };
+        for mod_id in ["a", "b"] {
+            let path = plugins.join(format!("{mod_id}.dll"));
+            fs::write(&path, mod_id).unwrap();
+            let sha = sha256_file(&path).unwrap();
+            record_mod(mod_id, "https://example.com", &sha, &path, &context).unwrap();
+        }
+
+        let lockfile = read_lockfile(profile).unwrap();
+        assert_eq!(lockfile.mods["a"].file, "BepInEx/plugins/a.dll");
+        assert_eq!(lockfile.mods["b"].size, 1);
+
+        remove_mods(profile, &["a".to_string()]).unwrap();
+        let lockfile = read_lockfile(profile).unwrap();
+        assert!(!lockfile.mods.contains_key("a"));
+        assert!(lockfile.mods.contains_key("b"));
+    }
+}
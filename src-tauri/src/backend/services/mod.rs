@@ -1,10 +1,29 @@
 pub mod bepinex_service;
+pub mod doorstop_service;
 pub mod epic_auth_service;
 pub mod epic_webview_login;
+pub mod extraction_policy;
 pub mod finder_service;
 pub mod http_download;
+pub mod http_retry;
 pub mod launch_service;
+pub mod lockfile_service;
+pub mod mod_cache_service;
+pub mod mod_dependency_service;
 pub mod mod_download_service;
+pub mod play_time_service;
+pub mod process_scan_service;
+pub mod profile_clone_service;
+pub mod profile_code_service;
+pub mod profile_integrity_service;
+pub mod profile_manifest_service;
+pub mod profile_migrations;
+pub mod profile_service;
 pub mod profile_zip_service;
+pub mod r2modman_service;
+pub mod snapshot_service;
 pub mod storage_service;
+pub mod storage_usage_service;
+#[cfg(target_os = "linux")]
+pub mod wine_service;
 pub mod xbox_service;
@@ -0,0 +1,246 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::http_download::sibling_path;
+use log::{debug, info, warn};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+const CACHE_DIR_NAME: &str = "mod-cache";
+const PROFILES_DIR_NAME: &str = "profiles";
+const LINK_SUFFIX: &str = "link";
+
+#[derive(Serialize)]
+pub struct ModCacheUsage {
+    pub blob_count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct ModCacheGcResult {
+    pub removed_count: usize,
+    pub freed_bytes: u64,
+}
+
+pub fn cache_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(CACHE_DIR_NAME))
+}
+
+pub fn profiles_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(PROFILES_DIR_NAME))
+}
+
+pub fn sha256_file(path: &Path) -> AppResult<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn normalize_checksum(checksum: &str) -> AppResult<String> {
+    let checksum = checksum.trim().to_lowercase();
+    if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::validation(format!(
+            "Invalid SHA-256 checksum: {checksum}"
+        )));
+    }
+    Ok(checksum)
+}
+
+fn blob_path(cache_dir: &Path, checksum: &str) -> PathBuf {
+    cache_dir.join(&checksum[..2]).join(checksum)
+}
+
+/// Hard-links `src` to `dst`, falling back to a copy when the filesystem (or a
+/// cross-volume layout) does not allow links. The target is replaced atomically.
+/// Returns whether a link was created.
+pub fn link_or_copy(src: &Path, dst: &Path) -> AppResult<bool> {
+    let staging = sibling_path(dst, LINK_SUFFIX);
+    fs::remove_file(&staging).ok();
+
+    let linked = match fs::hard_link(src, &staging) {
+        Ok(()) => true,
+        Err(e) => {
+            debug!("Hard link failed ({}), copying {:?} instead", e, src);
+            fs::copy(src, &staging)?;
+            false
+        }
+    };
+
+    if let Err(e) = fs::rename(&staging, dst) {
+        fs::remove_file(&staging).ok();
+        return Err(e.into());
+    }
+    Ok(linked)
+}
+
+/// Materialises a cached blob at `dest_path`. Returns `false` when the blob is
+/// missing or no longer matches its checksum.
+pub fn restore_from_cache(cache_dir: &Path, checksum: &str, dest_path: &Path) -> AppResult<bool> {
+    let checksum = normalize_checksum(checksum)?;
+    let blob = blob_path(cache_dir, &checksum);
+    if !blob.is_file() {
+        return Ok(false);
+    }
+
+    if sha256_file(&blob)? != checksum {
+        warn!("Removing corrupt mod cache blob {:?}", blob);
+        fs::remove_file(&blob).ok();
+        return Ok(false);
+    }
+
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    link_or_copy(&blob, dest_path)?;
+    Ok(true)
+}
+
+/// Adds an already verified file to the cache if its blob is not present yet.
+pub fn store_in_cache(cache_dir: &Path, checksum: &str, source: &Path) -> AppResult<()> {
+    let checksum = normalize_checksum(checksum)?;
+    let blob = blob_path(cache_dir, &checksum);
+    if blob.is_file() {
+        return Ok(());
+    }
+
+    if let Some(parent) = blob.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    link_or_copy(source, &blob)?;
+    debug!("Cached mod blob {}", checksum);
+    Ok(())
+}
+
+fn list_blobs(cache_dir: &Path) -> AppResult<Vec<(PathBuf, u64)>> {
+    let mut blobs = Vec::new();
+    let Ok(shards) = fs::read_dir(cache_dir) else {
+        return Ok(blobs);
+    };
+
+    for shard in shards {
+        let shard = shard?;
+        if !shard.file_type()?.is_dir() {
+            continue;
+        }
+        for blob in fs::read_dir(shard.path())? {
+            let blob = blob?;
+            let metadata = blob.metadata()?;
+            if metadata.is_file() && normalize_checksum(&blob.file_name().to_string_lossy()).is_ok()
+            {
+                blobs.push((blob.path(), metadata.len()));
+            }
+        }
+    }
+    Ok(blobs)
+}
+
+pub fn cache_usage(cache_dir: &Path) -> AppResult<ModCacheUsage> {
+    let blobs = list_blobs(cache_dir)?;
+    Ok(ModCacheUsage {
+        blob_count: blobs.len(),
+        total_bytes: blobs.iter().map(|(_, size)| size).sum(),
+    })
+}
+
+/// Removes every blob whose content is not present in any profile's
+/// `BepInEx/plugins` directory. Only profile files whose size matches a blob
+/// are hashed.
+pub fn collect_garbage(cache_dir: &Path, profiles_dir: &Path) -> AppResult<ModCacheGcResult> {
+    let blobs = list_blobs(cache_dir)?;
+    let blob_sizes: HashSet<u64> = blobs.iter().map(|(_, size)| *size).collect();
+
+    let mut referenced = HashSet::new();
+    if let Ok(profiles) = fs::read_dir(profiles_dir) {
+        for profile in profiles {
+            let plugins_dir = profile?.path().join("BepInEx").join("plugins");
+            for file in walk_files(&plugins_dir)? {
+                let size = fs::metadata(&file)?.len();
+                if blob_sizes.contains(&size) {
+                    referenced.insert(sha256_file(&file)?);
+                }
+            }
+        }
+    }
+
+    let mut result = ModCacheGcResult {
+        removed_count: 0,
+        freed_bytes: 0,
+    };
+    for (blob, size) in blobs {
+        let checksum = blob
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if referenced.contains(&checksum) {
+            continue;
+        }
+        fs::remove_file(&blob)?;
+        result.removed_count += 1;
+        result.freed_bytes += size;
+    }
+
+    info!(
+        "Mod cache GC removed {} blobs ({} bytes)",
+        result.removed_count, result.freed_bytes
+    );
+    Ok(result)
+}
+
+pub fn walk_files(dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(files);
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            files.extend(walk_files(&entry.path())?);
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_must_be_sha256_hex() {
+        assert!(normalize_checksum("../../etc/passwd").is_err());
+        assert!(normalize_checksum(&"A".repeat(64)).is_ok());
+    }
+
+    #[test]
+    fn cached_blob_round_trips_and_gc_removes_unreferenced() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let cache = root.join("cache");
+        let profiles = root.join("profiles");
+        let source = root.join("Mod.dll");
+        fs::write(&source, b"mod bytes").unwrap();
+        let checksum = sha256_file(&source).unwrap();
+
+        store_in_cache(&cache, &checksum, &source).unwrap();
+        assert_eq!(cache_usage(&cache).unwrap().blob_count, 1);
+
+        let plugin = profiles
+            .join("p1")
+            .join("BepInEx")
+            .join("plugins")
+            .join("Mod.dll");
+        assert!(restore_from_cache(&cache, &checksum, &plugin).unwrap());
+        assert_eq!(fs::read(&plugin).unwrap(), b"mod bytes");
+
+        assert_eq!(collect_garbage(&cache, &profiles).unwrap().removed_count, 0);
+        fs::remove_file(&plugin).unwrap();
+        assert_eq!(collect_garbage(&cache, &profiles).unwrap().removed_count, 1);
+    }
+}
@@ -0,0 +1,593 @@
+use crate::backend::error::{AppError, AppResult};
+use log::debug;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyType {
+    Required,
+    Optional,
+    Conflict,
+}
+
+/// Mirrors `ModDependency` in `features/mods/schema.ts`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModDependency {
+    pub mod_id: String,
+    pub version_constraint: String,
+    #[serde(rename = "type")]
+    pub kind: DependencyType,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CatalogVersion {
+    pub version: String,
+    #[serde(default)]
+    pub dependencies: Vec<ModDependency>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InstalledMod {
+    pub mod_id: String,
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveRequest {
+    pub mod_id: String,
+    pub version: String,
+    #[serde(default)]
+    pub installed: Vec<InstalledMod>,
+    /// Known versions (and their dependencies) for every mod the resolver may visit.
+    pub catalog: HashMap<String, Vec<CatalogVersion>>,
+    #[serde(default = "default_include_optional")]
+    pub include_optional: bool,
+}
+
+fn default_include_optional() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlannedMod {
+    pub mod_id: String,
+    pub version: String,
+    pub required_by: Option<String>,
+    pub optional: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlannedUpgrade {
+    pub mod_id: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub required_by: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolveIssueKind {
+    Conflict,
+    Unsatisfiable,
+    UnknownMod,
+    InvalidConstraint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ResolveIssue {
+    pub kind: ResolveIssueKind,
+    pub mod_id: String,
+    pub constraint: Option<String>,
+    pub required_by: Option<String>,
+    pub installed_version: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct InstallPlan {
+    pub additions: Vec<PlannedMod>,
+    pub upgrades: Vec<PlannedUpgrade>,
+    /// Blocking problems; the plan must not be applied while this is non-empty.
+    pub conflicts: Vec<ResolveIssue>,
+    /// Optional dependencies that could not be satisfied and were left out.
+    pub skipped_optional: Vec<ResolveIssue>,
+}
+
+struct Selection {
+    version: String,
+    installed_version: Option<String>,
+    required_by: Option<String>,
+    optional: bool,
+}
+
+struct Constraint {
+    range: String,
+    required_by: String,
+}
+
+struct Resolver<'a> {
+    catalog: &'a HashMap<String, Vec<CatalogVersion>>,
+    include_optional: bool,
+    selected: BTreeMap<String, Selection>,
+    constraints: HashMap<String, Vec<Constraint>>,
+    plan: InstallPlan,
+}
+
+pub fn resolve_install(request: &ResolveRequest) -> AppResult<InstallPlan> {
+    let target_known = request
+        .catalog
+        .get(&request.mod_id)
+        .is_some_and(|versions| versions.iter().any(|v| v.version == request.version));
+    if !target_known {
+        return Err(AppError::validation(format!(
+            "Mod {}@{} is not present in the provided catalog",
+            request.mod_id, request.version
+        )));
+    }
+
+    let mut resolver = Resolver {
+        catalog: &request.catalog,
+        include_optional: request.include_optional,
+        selected: BTreeMap::new(),
+        constraints: HashMap::new(),
+        plan: InstallPlan::default(),
+    };
+
+    for installed in &request.installed {
+        resolver.selected.insert(
+            installed.mod_id.clone(),
+            Selection {
+                version: installed.version.clone(),
+                installed_version: Some(installed.version.clone()),
+                required_by: None,
+                optional: false,
+            },
+        );
+    }
+
+    resolver.select(&request.mod_id, &request.version, None, false);
+    resolver.walk(VecDeque::from([(
+        request.mod_id.clone(),
+        request.version.clone(),
+    )]));
+    resolver.check_final_selection();
+    Ok(resolver.finish())
+}
+
+impl Resolver<'_> {
+    fn dependencies_of(&self, mod_id: &str, version: &str) -> &[ModDependency] {
+        self.catalog
+            .get(mod_id)
+            .and_then(|versions| versions.iter().find(|v| v.version == version))
+            .map(|v| v.dependencies.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn select(&mut self, mod_id: &str, version: &str, required_by: Option<&str>, optional: bool) {
+        let entry = self
+            .selected
+            .entry(mod_id.to_string())
+            .or_insert_with(|| Selection {
+                version: version.to_string(),
+                installed_version: None,
+                required_by: required_by.map(str::to_string),
+                optional,
+            });
+        entry.version = version.to_string();
+        if entry.installed_version.as_deref() != Some(version) {
+            entry.required_by = required_by.map(str::to_string);
+            entry.optional = optional;
+        }
+    }
+
+    fn walk(&mut self, mut queue: VecDeque<(String, String)>) {
+        while let Some((mod_id, version)) = queue.pop_front() {
+            let dependencies = self.dependencies_of(&mod_id, &version).to_vec();
+            for dependency in dependencies {
+                if dependency.kind == DependencyType::Optional && !self.include_optional {
+                    continue;
+                }
+                if let Some(next) = self.apply_dependency(&mod_id, &dependency) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    /// Applies one dependency edge, returning a mod version whose own
+    /// dependencies still need to be walked.
+    fn apply_dependency(
+        &mut self,
+        parent: &str,
+        dependency: &ModDependency,
+    ) -> Option<(String, String)> {
+        let mod_id = &dependency.mod_id;
+        let range = dependency.version_constraint.as_str();
+        let optional = dependency.kind == DependencyType::Optional;
+        let issue = |kind, installed_version: Option<&str>, reason: String| ResolveIssue {
+            kind,
+            mod_id: mod_id.clone(),
+            constraint: Some(range.to_string()),
+            required_by: Some(parent.to_string()),
+            installed_version: installed_version.map(str::to_string),
+            reason,
+        };
+
+        // Conflicts are evaluated once the whole selection is known.
+        if dependency.kind == DependencyType::Conflict {
+            return None;
+        }
+
+        let current = self.selected.get(mod_id).map(|s| s.version.clone());
+
+        if let Err(reason) = parse_constraint(range) {
+            self.report(
+                optional,
+                issue(ResolveIssueKind::InvalidConstraint, None, reason),
+            );
+            return None;
+        }
+
+        self.constraints
+            .entry(mod_id.clone())
+            .or_default()
+            .push(Constraint {
+                range: range.to_string(),
+                required_by: parent.to_string(),
+            });
+
+        if let Some(current) = &current
+            && self.satisfies_all(mod_id, current)
+        {
+            return None;
+        }
+
+        let Some(versions) = self.catalog.get(mod_id) else {
+            self.report(
+                optional,
+                issue(
+                    ResolveIssueKind::UnknownMod,
+                    current.as_deref(),
+                    format!("{mod_id} is required by {parent} but is not in the catalog"),
+                ),
+            );
+            return None;
+        };
+
+        let Some(best) = sorted_versions(versions)
+            .into_iter()
+            .find(|candidate| self.satisfies_all(mod_id, candidate))
+        else {
+            let requirements = self.constraints[mod_id]
+                .iter()
+                .map(|c| format!("{} ({})", c.range, c.required_by))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.report(
+                optional,
+                issue(
+                    ResolveIssueKind::Unsatisfiable,
+                    current.as_deref(),
+                    format!("No version of {mod_id} satisfies {requirements}"),
+                ),
+            );
+            return None;
+        };
+
+        debug!("Resolved {} to {} for {}", mod_id, best, parent);
+        self.select(mod_id, &best, Some(parent), optional);
+        Some((mod_id.clone(), best))
+    }
+
+    fn satisfies_all(&self, mod_id: &str, version: &str) -> bool {
+        self.constraints.get(mod_id).is_none_or(|constraints| {
+            constraints
+                .iter()
+                .all(|c| version_satisfies(version, &c.range).unwrap_or(false))
+        })
+    }
+
+    fn report(&mut self, optional: bool, issue: ResolveIssue) {
+        if optional {
+            self.plan.skipped_optional.push(issue);
+        } else {
+            self.plan.conflicts.push(issue);
+        }
+    }
+
+    /// Checks conflict declarations against the final selection, and makes sure
+    /// an upgrade does not break an installed mod that depends on it.
+    fn check_final_selection(&mut self) {
+        let mut issues = Vec::new();
+
+        for (dependent, selection) in &self.selected {
+            let dependent_unchanged =
+                selection.installed_version.as_deref() == Some(selection.version.as_str());
+            for dependency in self.dependencies_of(dependent, &selection.version) {
+                let Some(target) = self.selected.get(&dependency.mod_id) else {
+                    continue;
+                };
+                let target_unchanged =
+                    target.installed_version.as_deref() == Some(target.version.as_str());
+                if target_unchanged
+                    && (dependent_unchanged || dependency.kind != DependencyType::Conflict)
+                {
+                    continue;
+                }
+
+                let range = &dependency.version_constraint;
+                let issue = |kind, reason| ResolveIssue {
+                    kind,
+                    mod_id: dependency.mod_id.clone(),
+                    constraint: Some(range.clone()),
+                    required_by: Some(dependent.clone()),
+                    installed_version: target.installed_version.clone(),
+                    reason,
+                };
+
+                match (dependency.kind, version_satisfies(&target.version, range)) {
+                    (DependencyType::Conflict, Ok(true)) => issues.push(issue(
+                        ResolveIssueKind::Conflict,
+                        format!(
+                            "{dependent}@{} conflicts with {}@{} ({range})",
+                            selection.version, dependency.mod_id, target.version
+                        ),
+                    )),
+                    (DependencyType::Required, Ok(false)) if dependent_unchanged => {
+                        issues.push(issue(
+                            ResolveIssueKind::Conflict,
+                            format!(
+                                "Installed {dependent}@{} requires {} {range}, but {} is planned",
+                                selection.version, dependency.mod_id, target.version
+                            ),
+                        ));
+                    }
+                    (DependencyType::Conflict, Err(reason)) => {
+                        issues.push(issue(ResolveIssueKind::InvalidConstraint, reason));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.plan.conflicts.extend(issues);
+    }
+
+    fn finish(mut self) -> InstallPlan {
+        for (mod_id, selection) in self.selected {
+            match selection.installed_version {
+                Some(installed) if installed == selection.version => {}
+                Some(installed) => self.plan.upgrades.push(PlannedUpgrade {
+                    mod_id,
+                    from_version: installed,
+                    to_version: selection.version,
+                    required_by: selection.required_by,
+                }),
+                None => self.plan.additions.push(PlannedMod {
+                    mod_id,
+                    version: selection.version,
+                    required_by: selection.required_by,
+                    optional: selection.optional,
+                }),
+            }
+        }
+        self.plan
+    }
+}
+
+/// Parses versions the way the mod API publishes them: an optional `v` prefix
+/// and missing minor/patch components are tolerated.
+fn parse_version(raw: &str) -> Option<Version> {
+    let trimmed = raw.trim().trim_start_matches(['v', 'V']);
+    if let Ok(version) = Version::parse(trimmed) {
+        return Some(version);
+    }
+    let parts = trimmed.split('.').count();
+    let padded = match parts {
+        1 => format!("{trimmed}.0.0"),
+        2 => format!("{trimmed}.0"),
+        _ => return None,
+    };
+    Version::parse(&padded).ok()
+}
+
+/// Translates an npm-style range (as used by the frontend's `semver`
+/// package) into one `VersionReq` per `||` alternative.
+fn parse_constraint(range: &str) -> Result<Vec<VersionReq>, String> {
+    let range = range.trim();
+    if range.is_empty() || range == "*" || range.eq_ignore_ascii_case("x") {
+        return Ok(vec![VersionReq::STAR]);
+    }
+
+    range
+        .split("||")
+        .map(|alternative| {
+            let normalized = normalize_comparators(alternative.trim());
+            VersionReq::parse(&normalized)
+                .map_err(|e| format!("Invalid version constraint '{range}': {e}"))
+        })
+        .collect()
+}
+
+fn normalize_comparators(alternative: &str) -> String {
+    if let Some((low, high)) = alternative.split_once(" - ") {
+        return format!(">={}, <={}", low.trim(), high.trim());
+    }
+
+    let mut comparators: Vec<String> = Vec::new();
+    let mut pending_operator = String::new();
+    for token in alternative.split_whitespace() {
+        let token = token.trim_end_matches(',');
+        if token.chars().all(|c| "<>=~^".contains(c)) {
+            pending_operator.push_str(token);
+            continue;
+        }
+
+        let mut comparator = std::mem::take(&mut pending_operator);
+        let starts_with_version = token.starts_with(|c: char| c.is_ascii_digit() || c == 'v');
+        if comparator.is_empty() && starts_with_version && !token.contains(['x', 'X', '*']) {
+            // A bare npm version means an exact match, unlike Cargo's caret default.
+            comparator.push('=');
+        }
+        comparator.push_str(token.trim_start_matches('v'));
+        comparators.push(comparator);
+    }
+    comparators.join(", ")
+}
+
+fn version_satisfies(version: &str, range: &str) -> Result<bool, String> {
+    let requirements = parse_constraint(range)?;
+    let Some(version) = parse_version(version) else {
+        return Ok(range.trim() == version.trim() || requirements == [VersionReq::STAR]);
+    };
+    Ok(requirements.iter().any(|req| req.matches(&version)))
+}
+
+fn sorted_versions(versions: &[CatalogVersion]) -> Vec<String> {
+    let mut sorted: Vec<&CatalogVersion> = versions.iter().collect();
+    sorted.sort_by(
+        |a, b| match (parse_version(&a.version), parse_version(&b.version)) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => b.version.cmp(&a.version),
+        },
+    );
+    sorted.into_iter().map(|v| v.version.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dep(mod_id: &str, range: &str, kind: DependencyType) -> ModDependency {
+        ModDependency {
+            mod_id: mod_id.to_string(),
+            version_constraint: range.to_string(),
+            kind,
+        }
+    }
+
+    fn version(version: &str, dependencies: Vec<ModDependency>) -> CatalogVersion {
+        CatalogVersion {
+            version: version.to_string(),
+            dependencies,
+        }
+    }
+
+    fn request(
+        catalog: Vec<(&str, Vec<CatalogVersion>)>,
+        installed: Vec<(&str, &str)>,
+    ) -> ResolveRequest {
+        ResolveRequest {
+            mod_id: "target".to_string(),
+            version: "2.0.0".to_string(),
+            installed: installed
+                .into_iter()
+                .map(|(mod_id, version)| InstalledMod {
+                    mod_id: mod_id.to_string(),
+                    version: version.to_string(),
+                })
+                .collect(),
+            catalog: catalog
+                .into_iter()
+                .map(|(id, versions)| (id.to_string(), versions))
+                .collect(),
+            include_optional: true,
+        }
+    }
+
+    #[test]
+    fn npm_ranges_are_translated() {
+        assert_eq!(version_satisfies("1.2.3", "1.2.3"), Ok(true));
+        assert_eq!(version_satisfies("1.2.4", "1.2.3"), Ok(false));
+        assert_eq!(version_satisfies("1.4.0", ">= 1.2.0 <2.0.0"), Ok(true));
+        assert_eq!(version_satisfies("3.0.0", "^1.0.0 || ^3.0.0"), Ok(true));
+        assert_eq!(version_satisfies("1.5.0", "1.0.0 - 1.4.0"), Ok(false));
+        assert_eq!(version_satisfies("v1.1", "~1.1.0"), Ok(true));
+        assert!(version_satisfies("1.0.0", ">>1").is_err());
+    }
+
+    #[test]
+    fn plans_additions_and_upgrades() {
+        let plan = resolve_install(&request(
+            vec![
+                (
+                    "target",
+                    vec![version(
+                        "2.0.0",
+                        vec![
+                            dep("lib", "^1.2.0", DependencyType::Required),
+                            dep("extra", "*", DependencyType::Optional),
+                        ],
+                    )],
+                ),
+                (
+                    "lib",
+                    vec![version("1.1.0", vec![]), version("1.3.0", vec![])],
+                ),
+                ("extra", vec![version("0.1.0", vec![])]),
+            ],
+            vec![("lib", "1.1.0")],
+        ))
+        .unwrap();
+
+        assert!(plan.conflicts.is_empty());
+        assert_eq!(plan.upgrades.len(), 1);
+        assert_eq!(plan.upgrades[0].to_version, "1.3.0");
+        let added: Vec<_> = plan.additions.iter().map(|m| m.mod_id.as_str()).collect();
+        assert_eq!(added, ["extra", "target"]);
+    }
+
+    #[test]
+    fn reports_conflicts_and_unsatisfiable_constraints() {
+        let plan = resolve_install(&request(
+            vec![
+                (
+                    "target",
+                    vec![version(
+                        "2.0.0",
+                        vec![
+                            dep("legacy", "*", DependencyType::Conflict),
+                            dep("lib", ">=5.0.0", DependencyType::Required),
+                        ],
+                    )],
+                ),
+                ("lib", vec![version("1.0.0", vec![])]),
+            ],
+            vec![("legacy", "0.9.0")],
+        ))
+        .unwrap();
+
+        let kinds: Vec<_> = plan.conflicts.iter().map(|issue| issue.kind).collect();
+        assert_eq!(
+            kinds,
+            [ResolveIssueKind::Unsatisfiable, ResolveIssueKind::Conflict]
+        );
+    }
+
+    #[test]
+    fn upgrade_that_breaks_installed_dependent_is_a_conflict() {
+        let plan = resolve_install(&request(
+            vec![
+                ("target", vec![version("2.0.0", vec![])]),
+                (
+                    "addon",
+                    vec![version(
+                        "1.0.0",
+                        vec![dep("target", "^1.0.0", DependencyType::Required)],
+                    )],
+                ),
+            ],
+            vec![("target", "1.0.0"), ("addon", "1.0.0")],
+        ))
+        .unwrap();
+
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].required_by.as_deref(), Some("addon"));
+    }
+}
@@ -1,10 +1,16 @@
 use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::http_download::sibling_path;
+use crate::backend::services::http_retry::{RetryPolicy, retry_transient, status_error};
+use crate::backend::services::lockfile_service::{self, ModLockContext};
+use crate::backend::services::mod_cache_service;
+use crate::backend::services::snapshot_service::{self, SnapshotReason};
+use crate::backend::state::archive_operations::CancellationToken;
 use futures_util::StreamExt;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use sha2::{Digest, Sha256};
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Runtime};
 use tauri_plugin_store::StoreExt;
@@ -12,109 +18,272 @@ use uuid::Uuid;
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+const TEMP_SUFFIX: &str = "download";
 
 #[derive(Clone, serde::Serialize)]
 pub struct ModDownloadProgress {
     pub mod_id: String,
+    pub job_id: Option<String>,
     pub downloaded: u64,
     pub total: Option<u64>,
     pub progress: f64,
     pub stage: String,
 }
 
-fn emit_progress<R: Runtime>(
-    app: &AppHandle<R>,
-    mod_id: &str,
-    downloaded: u64,
-    total: Option<u64>,
-    stage: &str,
-) {
-    let progress = total
-        .map(|t| downloaded as f64 / t as f64 * 100.0)
-        .unwrap_or(0.0);
-
-    if let Err(e) = app.emit(
-        "mod-download-progress",
-        ModDownloadProgress {
-            mod_id: mod_id.to_string(),
-            downloaded,
-            total,
-            progress,
-            stage: stage.to_string(),
-        },
-    ) {
-        error!("Failed to emit download progress: {}", e);
+struct ProgressEmitter<'a, R: Runtime> {
+    app: &'a AppHandle<R>,
+    mod_id: &'a str,
+    job_id: Option<&'a str>,
+}
+
+impl<R: Runtime> ProgressEmitter<'_, R> {
+    fn emit(&self, downloaded: u64, total: Option<u64>, stage: &str) {
+        let progress = total
+            .map(|t| downloaded as f64 / t as f64 * 100.0)
+            .unwrap_or(0.0);
+
+        if let Err(e) = self.app.emit(
+            "mod-download-progress",
+            ModDownloadProgress {
+                mod_id: self.mod_id.to_string(),
+                job_id: self.job_id.map(str::to_string),
+                downloaded,
+                total,
+                progress,
+                stage: stage.to_string(),
+            },
+        ) {
+            error!("Failed to emit download progress: {}", e);
+        }
+    }
+}
+
+/// Deletes the temp file when dropped unless it was renamed into place, so a
+/// failed, mismatched or cancelled download never leaves partial data behind.
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            persisted: false,
+        }
     }
+
+    fn persist(mut self, dest_path: &Path) -> AppResult<()> {
+        fs::rename(&self.path, dest_path)?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModDownloadRequest {
+    pub mod_id: String,
+    pub url: String,
+    pub destination: String,
+    pub expected_checksum: String,
+    /// When set, the verified download is recorded in the profile's `starlight.lock`.
+    pub lock: Option<ModLockContext>,
 }
 
 pub async fn download_mod<R: Runtime>(
     app: AppHandle<R>,
-    mod_id: String,
-    url: String,
-    destination: String,
-    expected_checksum: String,
+    request: ModDownloadRequest,
+    retry: RetryPolicy,
+    job_id: Option<String>,
+    cancel: &CancellationToken,
 ) -> AppResult<()> {
+    cancel.check()?;
+    let ModDownloadRequest {
+        mod_id,
+        url,
+        destination,
+        expected_checksum,
+        lock,
+    } = request;
+
+    if let Some(profile_dir) = target_profile_dir(&destination, lock.as_ref()) {
+        let is_update = installed_version(&profile_dir, &mod_id)
+            .is_some_and(|installed| lock.as_ref().is_none_or(|lock| lock.version != installed));
+        if is_update {
+            snapshot_service::snapshot_before(&app, &profile_dir, SnapshotReason::BeforeModUpdate);
+        }
+    }
+
     let dest_path = Path::new(&destination);
     if let Some(parent) = dest_path.parent() {
         fs::create_dir_all(parent)?;
     }
 
-    let tracking_id = get_tracking_id(&app)?;
+    let progress = ProgressEmitter {
+        app: &app,
+        mod_id: &mod_id,
+        job_id: job_id.as_deref(),
+    };
+
+    let cache_dir = mod_cache_service::cache_dir(&app)?;
+    let restored =
+        match mod_cache_service::restore_from_cache(&cache_dir, &expected_checksum, dest_path) {
+            Ok(restored) => restored,
+            Err(e) => {
+                warn!("Failed to restore {} from mod cache: {}", mod_id, e);
+                false
+            }
+        };
+
+    if restored {
+        info!("Mod restored from cache: {} -> {:?}", mod_id, dest_path);
+    } else {
+        let tracking_id = get_tracking_id(&app)?;
+
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+
+        retry_transient(
+            &retry,
+            || {
+                download_attempt(
+                    &progress,
+                    &client,
+                    &url,
+                    &tracking_id,
+                    dest_path,
+                    &expected_checksum,
+                    cancel,
+                )
+            },
+            |_| progress.emit(0, None, "retrying"),
+        )
+        .await?;
+
+        if let Err(e) = mod_cache_service::store_in_cache(&cache_dir, &expected_checksum, dest_path)
+        {
+            warn!("Failed to add {} to mod cache: {}", mod_id, e);
+        }
+        info!("Mod download completed: {} -> {:?}", mod_id, dest_path);
+    }
+
+    if let Some(context) = &lock {
+        lockfile_service::record_mod(&mod_id, &url, &expected_checksum, dest_path, context)?;
+    }
+
+    let size = fs::metadata(dest_path).map(|m| m.len()).unwrap_or(0);
+    progress.emit(size, Some(size), "complete");
+    Ok(())
+}
+
+/// The profile a download installs into: the lock context's profile, or the
+/// profile whose `BepInEx/plugins` directory contains `destination`.
+pub fn target_profile_dir(destination: &str, lock: Option<&ModLockContext>) -> Option<PathBuf> {
+    if let Some(lock) = lock {
+        return Some(PathBuf::from(&lock.profile_path));
+    }
+
+    let plugins = Path::new(destination).parent()?;
+    let bepinex = plugins.parent()?;
+    let profile = bepinex.parent()?;
+    let in_plugins = plugins.file_name()?.eq_ignore_ascii_case("plugins")
+        && bepinex.file_name()?.eq_ignore_ascii_case("bepinex");
+    (in_plugins && profile.join("metadata.json").is_file()).then(|| profile.to_path_buf())
+}
 
-    let client = reqwest::Client::builder()
-        .connect_timeout(CONNECT_TIMEOUT)
-        .timeout(REQUEST_TIMEOUT)
-        .build()?;
+/// The version `metadata.json` lists for `mod_id`, if the profile has it.
+fn installed_version(profile_dir: &Path, mod_id: &str) -> Option<String> {
+    let content = fs::read_to_string(profile_dir.join("metadata.json")).ok()?;
+    let metadata: serde_json::Value = serde_json::from_str(&content).ok()?;
+    metadata
+        .get("mods")?
+        .as_array()?
+        .iter()
+        .find(|entry| entry.get("mod_id").and_then(|id| id.as_str()) == Some(mod_id))?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
 
-    emit_progress(&app, &mod_id, 0, None, "connecting");
+async fn download_attempt<R: Runtime>(
+    progress: &ProgressEmitter<'_, R>,
+    client: &reqwest::Client,
+    url: &str,
+    tracking_id: &str,
+    dest_path: &Path,
+    expected_checksum: &str,
+    cancel: &CancellationToken,
+) -> AppResult<()> {
+    cancel.check()?;
+    progress.emit(0, None, "connecting");
 
     let response = client
-        .get(&url)
-        .header("X-Starlight-ID", &tracking_id)
+        .get(url)
+        .header("X-Starlight-ID", tracking_id)
         .send()
         .await?;
 
     if !response.status().is_success() {
-        return Err(AppError::other(format!(
-            "Download failed: HTTP {}",
-            response.status()
-        )));
+        return Err(status_error(response.status(), response.headers()));
     }
 
     let total_size = response.content_length();
     debug!("Download size: {:?}", total_size);
 
+    let temp_file = TempFile::new(sibling_path(dest_path, TEMP_SUFFIX));
+    let (computed_checksum, downloaded) =
+        stream_to_file(progress, response, &temp_file.path, total_size, cancel).await?;
+
+    progress.emit(downloaded, total_size, "verifying");
+    if computed_checksum != expected_checksum.to_lowercase() {
+        return Err(AppError::validation(format!(
+            "Checksum mismatch: expected {}, got {}",
+            expected_checksum, computed_checksum
+        )));
+    }
+
+    progress.emit(downloaded, total_size, "writing");
+    temp_file.persist(dest_path)
+}
+
+/// Streams the response body into `temp_path`, returning the SHA-256 hex digest
+/// and the number of bytes written.
+async fn stream_to_file<R: Runtime>(
+    progress: &ProgressEmitter<'_, R>,
+    response: reqwest::Response,
+    temp_path: &Path,
+    total_size: Option<u64>,
+    cancel: &CancellationToken,
+) -> AppResult<(String, u64)> {
+    let mut file = File::create(temp_path)?;
     let mut hasher = Sha256::new();
     let mut downloaded: u64 = 0;
-    let mut buffer = Vec::new();
 
-    emit_progress(&app, &mod_id, 0, total_size, "downloading");
+    progress.emit(0, total_size, "downloading");
 
     let mut stream = response.bytes_stream();
     while let Some(chunk) = stream.next().await {
+        cancel.check()?;
         let chunk = chunk?;
         hasher.update(&chunk);
-        buffer.extend_from_slice(&chunk);
+        file.write_all(&chunk)?;
         downloaded += chunk.len() as u64;
-        emit_progress(&app, &mod_id, downloaded, total_size, "downloading");
-    }
-
-    emit_progress(&app, &mod_id, downloaded, total_size, "verifying");
-    let computed_checksum = format!("{:x}", hasher.finalize());
-    if computed_checksum != expected_checksum.to_lowercase() {
-        return Err(AppError::validation(format!(
-            "Checksum mismatch: expected {}, got {}",
-            expected_checksum, computed_checksum
-        )));
+        progress.emit(downloaded, total_size, "downloading");
     }
 
-    emit_progress(&app, &mod_id, downloaded, total_size, "writing");
-    let mut file = File::create(dest_path)?;
-    file.write_all(&buffer)?;
-
-    emit_progress(&app, &mod_id, downloaded, total_size, "complete");
-    info!("Mod download completed: {} -> {:?}", mod_id, dest_path);
-    Ok(())
+    file.sync_all()?;
+    Ok((format!("{:x}", hasher.finalize()), downloaded))
 }
 
 fn get_tracking_id<R: Runtime>(app: &AppHandle<R>) -> AppResult<String> {
@@ -135,3 +304,33 @@ fn get_tracking_id<R: Runtime>(app: &AppHandle<R>) -> AppResult<String> {
         .map_err(|e| AppError::state(format!("Failed to save registry store: {e}")))?;
     Ok(new_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_are_detected_from_profile_metadata() {
+        let temp = tempfile::tempdir().unwrap();
+        let profile = temp.path().join("profile");
+        let plugins = profile.join("BepInEx").join("plugins");
+        fs::create_dir_all(&plugins).unwrap();
+        fs::write(
+            profile.join("metadata.json"),
+            r#"{"mods":[{"mod_id":"a","version":"1.0.0","file":"A.dll"}]}"#,
+        )
+        .unwrap();
+
+        let destination = plugins.join("A.dll").to_string_lossy().to_string();
+        assert_eq!(
+            target_profile_dir(&destination, None),
+            Some(profile.clone())
+        );
+        assert_eq!(
+            target_profile_dir(&temp.path().join("A.dll").to_string_lossy(), None),
+            None
+        );
+        assert_eq!(installed_version(&profile, "a").as_deref(), Some("1.0.0"));
+        assert_eq!(installed_version(&profile, "b"), None);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::backend::error::AppResult;
+use crate::backend::services::profile_service::{self, Profile};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+pub const SESSION_LOG_FILE_NAME: &str = "sessions.jsonl";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlaySession {
+    /// Unix time in milliseconds.
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub duration_ms: u64,
+    /// Added to `total_play_time`: the time since play time was last counted
+    /// for the profile. Time already covered by another instance is not
+    /// counted twice, and an instance that outlived an earlier one also gets
+    /// the time that one had not counted yet, so this can exceed
+    /// `duration_ms`.
+    pub counted_ms: u64,
+    pub platform: String,
+}
+
+pub fn record_launch(profiles_dir: &Path, profile_id: &str, launched_at: u64) -> AppResult<()> {
+    profile_service::modify_profile(profiles_dir, profile_id, |profile| {
+        profile.last_launched_at = Some(launched_at);
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Appends `session` to the profile's session log and adds its counted time
+/// to `total_play_time`.
+pub fn record_session(
+    profiles_dir: &Path,
+    profile_id: &str,
+    session: &PlaySession,
+) -> AppResult<Profile> {
+    profile_service::modify_profile(profiles_dir, profile_id, |profile| {
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Path::new(&profile.path).join(SESSION_LOG_FILE_NAME))?;
+        writeln!(log, "{}", serde_json::to_string(session)?)?;
+        log.sync_data()?;
+
+        profile.total_play_time = Some(profile.total_play_time.unwrap_or(0) + session.counted_ms);
+        Ok(())
+    })
+}
+
+/// Oldest first. Unreadable lines, e.g. one cut short by a crash, are skipped.
+pub fn list_sessions(profile_dir: &Path) -> AppResult<Vec<PlaySession>> {
+    let content = match fs::read_to_string(profile_dir.join(SESSION_LOG_FILE_NAME)) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                warn!("Skipping malformed play session entry: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_accumulate_play_time() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let profile = profile_service::create_profile(root, "Play").unwrap();
+
+        for (started_at, counted_ms) in [(1_000, 500), (5_000, 250)] {
+            let session = PlaySession {
+                started_at,
+                ended_at: started_at + 500,
+                duration_ms: 500,
+                counted_ms,
+                platform: "steam".to_string(),
+            };
+            record_session(root, &profile.id, &session).unwrap();
+        }
+        record_launch(root, &profile.id, 9_000).unwrap();
+
+        let updated = profile_service::get_profile(root, &profile.id).unwrap();
+        assert_eq!(updated.total_play_time, Some(750));
+        assert_eq!(updated.last_launched_at, Some(9_000));
+
+        let log = Path::new(&profile.path).join(SESSION_LOG_FILE_NAME);
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&log)
+            .unwrap()
+            .write_all(b"{\"started_at\":")
+            .unwrap();
+        let sessions = list_sessions(Path::new(&profile.path)).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].started_at, 5_000);
+    }
+}
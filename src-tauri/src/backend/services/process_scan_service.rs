@@ -0,0 +1,259 @@
+use std::process::Command;
+
+pub const GAME_PROCESS_NAME: &str = "Among Us.exe";
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Whether `candidate` names `image_name`, either directly or as the last
+/// component of a Unix or Windows path.
+fn matches_image_name(candidate: &str, image_name: &str) -> bool {
+    let file_name = candidate
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(candidate)
+        .trim();
+    file_name.eq_ignore_ascii_case(image_name)
+}
+
+/// PIDs of running processes whose executable is `image_name`, used to find
+/// game processes Starlight did not spawn itself. Under Wine the Windows
+/// executable name shows up as the process name or first argument.
+#[cfg(target_os = "linux")]
+pub fn find_processes(image_name: &str) -> Vec<u32> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut pids: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| {
+            let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).unwrap_or_default();
+            if matches_image_name(&comm, image_name) {
+                return true;
+            }
+            std::fs::read(format!("/proc/{pid}/cmdline"))
+                .ok()
+                .and_then(|cmdline| {
+                    let first = cmdline.split(|byte| *byte == 0).next()?.to_vec();
+                    String::from_utf8(first).ok()
+                })
+                .is_some_and(|first| matches_image_name(&first, image_name))
+        })
+        .collect();
+    pids.sort_unstable();
+    pids
+}
+
+#[cfg(windows)]
+pub fn find_processes(image_name: &str) -> Vec<u32> {
+    use std::os::windows::process::CommandExt;
+
+    let Ok(output) = Command::new("tasklist")
+        .args([
+            "/FI",
+            &format!("IMAGENAME eq {image_name}"),
+            "/FO",
+            "CSV",
+            "/NH",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    let mut pids: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split("\",\"");
+            let name = fields.next()?.trim_start_matches('"');
+            let pid = fields.next()?;
+            matches_image_name(name, image_name)
+                .then(|| pid.parse().ok())
+                .flatten()
+        })
+        .collect();
+    pids.sort_unstable();
+    pids
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn find_processes(image_name: &str) -> Vec<u32> {
+    let Ok(output) = Command::new("ps").args(["-axo", "pid=,comm="]).output() else {
+        return Vec::new();
+    };
+
+    let mut pids: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim().split_once(' ')?;
+            matches_image_name(command, image_name)
+                .then(|| pid.parse().ok())
+                .flatten()
+        })
+        .collect();
+    pids.sort_unstable();
+    pids
+}
+
+/// Whether `pid` is still a process running `image_name`. PIDs of processes
+/// we did not spawn can be reused once they exit, so this is checked again
+/// before signalling them.
+pub fn is_running_as(pid: u32, image_name: &str) -> bool {
+    find_processes(image_name).contains(&pid)
+}
+
+#[cfg(target_os = "linux")]
+pub fn is_process_running(pid: u32) -> bool {
+    // Zombies still have a /proc entry but are no longer running.
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            let state = stat.rsplit_once(')')?.1.trim_start().chars().next()?;
+            Some(state != 'Z' && state != 'X')
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+pub fn is_process_running(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{pid}\"")))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn is_process_running(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Asks `pid` to exit: SIGTERM on Unix, a close request on Windows. Returns
+/// whether the request was delivered.
+#[cfg(windows)]
+pub fn request_exit(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+
+    Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+pub fn request_exit(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Like [`request_exit`], for every process in the group led by `pgid`.
+/// Under Wine or Proton the spawned process is only a wrapper, so the game is
+/// reached through the process group it was started in.
+#[cfg(unix)]
+pub fn request_group_exit(pgid: u32) -> bool {
+    Command::new("kill")
+        .args(["-TERM", "--", &format!("-{pgid}")])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Like [`force_exit`], for every process in the group led by `pgid`.
+#[cfg(unix)]
+pub fn force_group_exit(pgid: u32) -> bool {
+    Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pgid}")])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Terminates `pid` without giving it a chance to clean up.
+#[cfg(windows)]
+pub fn force_exit(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+
+    Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+pub fn force_exit(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-KILL", &pid.to_string()])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_names_match_paths_and_case() {
+        assert!(matches_image_name("Among Us.exe", GAME_PROCESS_NAME));
+        assert!(matches_image_name(
+            "Z:\\games\\Among Us\\among us.exe",
+            GAME_PROCESS_NAME
+        ));
+        assert!(matches_image_name(
+            "/games/Among Us/Among Us.exe\n",
+            GAME_PROCESS_NAME
+        ));
+        assert!(!matches_image_name("Among Us", GAME_PROCESS_NAME));
+    }
+
+    #[test]
+    fn current_process_is_running() {
+        assert!(is_process_running(std::process::id()));
+        assert!(!is_running_as(std::process::id(), GAME_PROCESS_NAME));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn group_exit_reaches_grandchildren() {
+        use std::os::unix::process::CommandExt;
+
+        let mut wrapper = Command::new("sh")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(std::process::Stdio::piped())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        std::io::BufRead::read_line(
+            &mut std::io::BufReader::new(wrapper.stdout.take().unwrap()),
+            &mut line,
+        )
+        .unwrap();
+        let grandchild: u32 = line.trim().parse().unwrap();
+
+        assert!(force_group_exit(wrapper.id()));
+        wrapper.wait().unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while is_process_running(grandchild) && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(!is_process_running(grandchild));
+    }
+}
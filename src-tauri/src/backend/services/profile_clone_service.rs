@@ -0,0 +1,285 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::mod_cache_service::{link_or_copy, walk_files};
+use crate::backend::services::profile_zip_service::{
+    build_sanitized_metadata, is_metadata_file, should_skip_export_file,
+};
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Component, Path};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directories whose files are shared between profiles through hard links.
+/// Files in them are only ever replaced by renaming a new file into place, so
+/// a write to one profile never reaches another through a shared link.
+const IMMUTABLE_DIRS: [&[&str]; 3] = [&["BepInEx", "plugins"], &["BepInEx", "core"], &["dotnet"]];
+
+#[derive(Serialize)]
+pub struct ProfileCloneResult {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub linked_files: usize,
+    pub copied_files: usize,
+    pub metadata: Value,
+}
+
+pub fn clone_profile(
+    profiles_dir: &Path,
+    source_path: &Path,
+    name: &str,
+) -> AppResult<ProfileCloneResult> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::validation("Profile name cannot be empty"));
+    }
+    if !source_path.is_dir() {
+        return Err(AppError::validation(format!(
+            "Profile directory does not exist: {}",
+            source_path.display()
+        )));
+    }
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let id = build_profile_id(name, created_at);
+    let destination = profiles_dir.join(&id);
+    if destination.exists() {
+        return Err(AppError::validation(format!(
+            "Profile directory already exists: {}",
+            destination.display()
+        )));
+    }
+
+    match clone_into(source_path, &destination, &id, name, created_at) {
+        Ok(result) => {
+            info!(
+                "Cloned profile {} -> {} ({} linked, {} copied)",
+                source_path.display(),
+                id,
+                result.linked_files,
+                result.copied_files
+            );
+            Ok(result)
+        }
+        Err(e) => {
+            if let Err(cleanup) = fs::remove_dir_all(&destination) {
+                warn!(
+                    "Failed to remove partial clone {:?}: {}",
+                    destination, cleanup
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+fn clone_into(
+    source_path: &Path,
+    destination: &Path,
+    id: &str,
+    name: &str,
+    created_at: u64,
+) -> AppResult<ProfileCloneResult> {
+    fs::create_dir_all(destination)?;
+    let mut linked_files = 0;
+    let mut copied_files = 0;
+
+    for file in walk_files(source_path)? {
+        let relative = file
+            .strip_prefix(source_path)
+            .map_err(|e| AppError::other(e.to_string()))?;
+        if should_skip_export_file(relative)
+            || (is_metadata_file(relative) && relative.components().count() == 1)
+        {
+            continue;
+        }
+
+        let target = destination.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if !is_immutable(relative) {
+            fs::copy(&file, &target)?;
+            copied_files += 1;
+        } else if link_or_copy(&file, &target)? {
+            linked_files += 1;
+        } else {
+            copied_files += 1;
+        }
+    }
+
+    let sanitized = build_sanitized_metadata(source_path)?;
+    let mut metadata: Map<String, Value> = serde_json::from_str(&sanitized)?;
+    let path = destination.to_string_lossy().to_string();
+    metadata.insert("id".to_string(), Value::String(id.to_string()));
+    metadata.insert("name".to_string(), Value::String(name.to_string()));
+    metadata.insert("path".to_string(), Value::String(path.clone()));
+    metadata.insert("created_at".to_string(), Value::from(created_at));
+    metadata.insert("total_play_time".to_string(), Value::from(0));
+    metadata.remove("last_launched_at");
+
+    let metadata = Value::Object(metadata);
+    fs::write(
+        destination.join("metadata.json"),
+        serde_json::to_string_pretty(&metadata)?,
+    )?;
+
+    Ok(ProfileCloneResult {
+        id: id.to_string(),
+        name: name.to_string(),
+        path,
+        linked_files,
+        copied_files,
+        metadata,
+    })
+}
+
+fn is_immutable(relative: &Path) -> bool {
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    IMMUTABLE_DIRS.iter().any(|prefix| {
+        parts.len() > prefix.len()
+            && prefix
+                .iter()
+                .zip(&parts)
+                .all(|(expected, part)| expected.eq_ignore_ascii_case(part))
+    })
+}
+
+/// Mirrors the frontend's `buildProfileId`: a slug of the name plus a
+/// millisecond timestamp.
+pub fn build_profile_id(name: &str, timestamp: u64) -> String {
+    let mut slug = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+
+    if slug.is_empty() {
+        format!("profile-{timestamp}")
+    } else {
+        format!("{slug}-{timestamp}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_ids_match_frontend_slugs() {
+        assert_eq!(
+            build_profile_id("My  Cool Profile!", 42),
+            "my-cool-profile-42"
+        );
+        assert_eq!(build_profile_id("???", 7), "profile-7");
+    }
+
+    #[test]
+    fn clone_links_plugins_and_resets_metadata() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let source = root.join("source");
+        fs::create_dir_all(source.join("BepInEx").join("plugins")).unwrap();
+        fs::create_dir_all(source.join("BepInEx").join("config")).unwrap();
+        fs::write(
+            source.join("BepInEx").join("plugins").join("Mod.dll"),
+            "dll",
+        )
+        .unwrap();
+        fs::write(source.join("BepInEx").join("config").join("mod.cfg"), "cfg").unwrap();
+        fs::write(
+            source.join("metadata.json"),
+            r#"{"id":"source","name":"Source","path":"/old","created_at":1,"total_play_time":99,"mods":[]}"#,
+        )
+        .unwrap();
+
+        let result = clone_profile(root, &source, "Copy").unwrap();
+        let clone = Path::new(&result.path);
+        assert!(
+            clone
+                .join("BepInEx")
+                .join("plugins")
+                .join("Mod.dll")
+                .is_file()
+        );
+        assert!(
+            clone
+                .join("BepInEx")
+                .join("config")
+                .join("mod.cfg")
+                .is_file()
+        );
+        assert_eq!(result.linked_files + result.copied_files, 2);
+        assert_eq!(result.metadata["id"], result.id.as_str());
+        assert_eq!(result.metadata["name"], "Copy");
+        assert_eq!(result.metadata["total_play_time"], 0);
+    }
+
+    #[test]
+    fn reinstalling_into_a_clone_leaves_the_source_unchanged() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let source = root.join("source");
+        let core = source.join("BepInEx").join("core");
+        let plugins = source.join("BepInEx").join("plugins");
+        fs::create_dir_all(&core).unwrap();
+        fs::create_dir_all(&plugins).unwrap();
+        fs::write(core.join("BepInEx.dll"), "core v1").unwrap();
+        fs::write(plugins.join("Mod.dll"), "mod v1").unwrap();
+        fs::write(source.join("metadata.json"), r#"{"name":"Source"}"#).unwrap();
+
+        let result = clone_profile(root, &source, "Copy").unwrap();
+        assert_eq!(result.copied_files + result.linked_files, 2);
+
+        let archive = root.join("bepinex.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        for name in ["BepInEx/core/BepInEx.dll", "BepInEx/plugins/Mod.dll"] {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut zip, b"v2").unwrap();
+        }
+        zip.finish().unwrap();
+        crate::backend::services::http_download::extract_zip(
+            &archive,
+            Path::new(&result.path),
+            &crate::backend::services::extraction_policy::ExtractionPolicy::default(),
+            &crate::backend::state::archive_operations::CancellationToken::default(),
+            |_, _| {},
+        )
+        .unwrap();
+
+        let clone = Path::new(&result.path).join("BepInEx");
+        assert_eq!(
+            fs::read(clone.join("core").join("BepInEx.dll")).unwrap(),
+            b"v2"
+        );
+        assert_eq!(fs::read(core.join("BepInEx.dll")).unwrap(), b"core v1");
+        assert_eq!(fs::read(plugins.join("Mod.dll")).unwrap(), b"mod v1");
+    }
+
+    #[test]
+    fn plugins_and_loader_files_are_shared() {
+        assert!(is_immutable(Path::new("BepInEx/plugins/Mod.dll")));
+        assert!(is_immutable(Path::new("BepInEx/core/BepInEx.dll")));
+        assert!(is_immutable(Path::new("dotnet/coreclr.dll")));
+        assert!(!is_immutable(Path::new("BepInEx/config/mod.cfg")));
+        assert!(!is_immutable(Path::new("doorstop_config.ini")));
+        assert!(!is_immutable(Path::new("BepInEx/plugins")));
+    }
+}
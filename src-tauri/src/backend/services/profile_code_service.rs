@@ -0,0 +1,244 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::profile_manifest_service::{self, ManifestMod};
+use crate::backend::services::profile_migrations;
+use crate::backend::services::profile_zip_service::build_sanitized_metadata;
+use base64::Engine;
+use flate2::Compression;
+use flate2::read::{DeflateDecoder, DeflateEncoder};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+const B64: base64::engine::GeneralPurpose = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+const CODE_PREFIX: &str = "SLP";
+const CODE_VERSION: u32 = 1;
+const CHECKSUM_LEN: usize = 8;
+const MAX_CODE_LEN: usize = 256 * 1024;
+const MAX_DECODED_BYTES: u64 = 4 * 1024 * 1024;
+const CONFIG_DIR: [&str; 2] = ["BepInEx", "config"];
+
+/// Everything a profile code carries. Config overrides are keyed by their
+/// path relative to `BepInEx/config`, `/`-separated.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProfileCodePayload {
+    pub metadata: Value,
+    pub mods: Vec<ManifestMod>,
+    #[serde(default)]
+    pub config_overrides: BTreeMap<String, String>,
+}
+
+pub fn build_payload(profile_dir: &Path, config_files: &[String]) -> AppResult<ProfileCodePayload> {
+    if !profile_dir.is_dir() {
+        return Err(AppError::validation(format!(
+            "Profile directory does not exist: {}",
+            profile_dir.display()
+        )));
+    }
+
+    let sanitized_metadata = build_sanitized_metadata(profile_dir)?;
+    let manifest = profile_manifest_service::build_manifest(profile_dir, &sanitized_metadata)?;
+
+    let mut config_overrides = BTreeMap::new();
+    for name in config_files {
+        let path = config_file_path(profile_dir, name)?;
+        let content = fs::read_to_string(&path).map_err(|e| {
+            AppError::validation(format!("Config file {name} cannot be shared: {e}"))
+        })?;
+        config_overrides.insert(name.replace('\\', "/"), content);
+    }
+
+    Ok(ProfileCodePayload {
+        metadata: serde_json::from_str(&sanitized_metadata)?,
+        mods: manifest.mods,
+        config_overrides,
+    })
+}
+
+/// Encodes a payload as `SLP<version>.<base64url(checksum || deflate(json))>`.
+pub fn encode_payload(payload: &ProfileCodePayload) -> AppResult<String> {
+    let json = serde_json::to_vec(payload)?;
+    let mut compressed = Vec::new();
+    DeflateEncoder::new(json.as_slice(), Compression::best()).read_to_end(&mut compressed)?;
+
+    let mut body = checksum(&compressed).to_vec();
+    body.extend_from_slice(&compressed);
+    Ok(format!("{CODE_PREFIX}{CODE_VERSION}.{}", B64.encode(body)))
+}
+
+/// Decodes and validates a profile code. Nothing is written to disk.
+pub fn decode_payload(code: &str) -> AppResult<ProfileCodePayload> {
+    let code = code.trim();
+    if code.len() > MAX_CODE_LEN {
+        return Err(AppError::validation("Profile code is too long"));
+    }
+
+    let (version, body) = code
+        .strip_prefix(CODE_PREFIX)
+        .and_then(|rest| rest.split_once('.'))
+        .ok_or_else(|| AppError::validation("Not a Starlight profile code"))?;
+    let version: u32 = version
+        .parse()
+        .map_err(|_| AppError::validation("Profile code has an invalid version"))?;
+    if version > CODE_VERSION {
+        return Err(AppError::validation(format!(
+            "Profile code version {version} was created by a newer version of Starlight"
+        )));
+    }
+    if version != CODE_VERSION {
+        return Err(AppError::validation(format!(
+            "Unsupported profile code version {version}"
+        )));
+    }
+
+    let body = B64
+        .decode(body)
+        .map_err(|e| AppError::validation(format!("Profile code is not valid base64: {e}")))?;
+    if body.len() <= CHECKSUM_LEN {
+        return Err(AppError::validation("Profile code is truncated"));
+    }
+    let (expected, compressed) = body.split_at(CHECKSUM_LEN);
+    if checksum(compressed) != expected {
+        return Err(AppError::validation(
+            "Profile code checksum mismatch; it may be incomplete or altered",
+        ));
+    }
+
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(MAX_DECODED_BYTES + 1)
+        .read_to_end(&mut json)?;
+    if json.len() as u64 > MAX_DECODED_BYTES {
+        return Err(AppError::validation(
+            "Profile code expands beyond the size limit",
+        ));
+    }
+
+    let payload: ProfileCodePayload = serde_json::from_slice(&json)?;
+    if !payload.metadata.is_object() {
+        return Err(AppError::validation(
+            "Profile code metadata must be an object",
+        ));
+    }
+    for name in payload.config_overrides.keys() {
+        validate_config_name(name)?;
+    }
+    Ok(payload)
+}
+
+/// Writes the decoded metadata and config overrides into `destination`.
+pub fn write_payload(
+    payload: &ProfileCodePayload,
+    destination: &Path,
+) -> AppResult<Option<String>> {
+    if let Some(metadata) = payload.metadata.as_object() {
+        profile_migrations::ensure_supported(metadata)?;
+    }
+    fs::create_dir_all(destination)?;
+    fs::write(
+        destination.join("metadata.json"),
+        serde_json::to_string_pretty(&payload.metadata)?,
+    )?;
+
+    for (name, content) in &payload.config_overrides {
+        let path = config_file_path(destination, name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+    }
+
+    info!(
+        "Wrote profile code into {} ({} mods, {} config overrides)",
+        destination.display(),
+        payload.mods.len(),
+        payload.config_overrides.len()
+    );
+    Ok(payload
+        .metadata
+        .get("name")
+        .and_then(Value::as_str)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty()))
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(bytes);
+    let mut out = [0; CHECKSUM_LEN];
+    out.copy_from_slice(&digest[..CHECKSUM_LEN]);
+    out
+}
+
+fn validate_config_name(name: &str) -> AppResult<()> {
+    let path = Path::new(name);
+    let safe = path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !safe || name.contains('\\') {
+        return Err(AppError::validation(format!(
+            "Unsafe config path in profile code: {name}"
+        )));
+    }
+    Ok(())
+}
+
+fn config_file_path(profile_dir: &Path, name: &str) -> AppResult<PathBuf> {
+    let name = name.replace('\\', "/");
+    validate_config_name(&name)?;
+    Ok(CONFIG_DIR
+        .iter()
+        .fold(profile_dir.to_path_buf(), |dir, part| dir.join(part))
+        .join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_payload() -> ProfileCodePayload {
+        ProfileCodePayload {
+            metadata: serde_json::json!({ "name": "Shared", "mods": [] }),
+            mods: Vec::new(),
+            config_overrides: BTreeMap::from([(
+                "mod.cfg".to_string(),
+                "[General]\nEnabled = true\n".to_string(),
+            )]),
+        }
+    }
+
+    #[test]
+    fn code_round_trips() {
+        let code = encode_payload(&sample_payload()).unwrap();
+        assert!(code.starts_with("SLP1."));
+        let decoded = decode_payload(&code).unwrap();
+        assert_eq!(decoded.metadata["name"], "Shared");
+        assert_eq!(decoded.config_overrides.len(), 1);
+    }
+
+    #[test]
+    fn tampered_or_future_codes_are_rejected() {
+        let code = encode_payload(&sample_payload()).unwrap();
+        let (_, body) = code.split_once('.').unwrap();
+
+        let mut bytes = B64.decode(body).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let tampered = format!("SLP1.{}", B64.encode(bytes));
+        assert!(decode_payload(&tampered).is_err());
+
+        assert!(decode_payload(&format!("SLP2.{body}")).is_err());
+        assert!(decode_payload("not a code").is_err());
+    }
+
+    #[test]
+    fn config_names_cannot_escape() {
+        assert!(validate_config_name("sub/mod.cfg").is_ok());
+        assert!(validate_config_name("../metadata.json").is_err());
+        assert!(validate_config_name("/etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::lockfile_service::{self, LockedMod};
+use crate::backend::services::mod_cache_service::{sha256_file, walk_files};
+use crate::backend::services::profile_service;
+use crate::backend::services::snapshot_service::{self, SnapshotReason};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+use tauri::{AppHandle, Runtime};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct IntegrityIssue {
+    pub mod_id: String,
+    pub version: String,
+    /// Path relative to the profile root, `/`-separated.
+    pub file: String,
+    /// Whether a checksum-verified copy can be downloaded again.
+    pub repairable: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    pub verified: Vec<String>,
+    /// Listed in `metadata.json` but the installed file is gone.
+    pub missing: Vec<IntegrityIssue>,
+    /// Present, but no longer matches the checksum recorded at download time.
+    pub modified: Vec<IntegrityIssue>,
+    /// Present, with no recorded checksum to compare against.
+    pub unverified: Vec<String>,
+    /// Plugin DLLs that no listed mod accounts for, e.g. manually added mods.
+    pub extra: Vec<String>,
+    /// Lockfile entries for mods that are no longer listed in `metadata.json`.
+    pub orphaned: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityRepairResult {
+    pub reinstalled: Vec<String>,
+    /// Mods whose file is gone and cannot be downloaded again.
+    pub unrecoverable: Vec<String>,
+    /// The unrecoverable mods dropped from `metadata.json`, only when the
+    /// caller confirmed it.
+    pub removed_from_metadata: Vec<String>,
+    pub pruned_lock_entries: Vec<String>,
+    pub failed: Vec<String>,
+    pub report: IntegrityReport,
+}
+
+fn installed_file(profile_mod_file: Option<&str>, locked: Option<&LockedMod>) -> Option<String> {
+    if let Some(locked) = locked {
+        return Some(locked.file.clone());
+    }
+    profile_mod_file.map(|file| format!("BepInEx/plugins/{file}"))
+}
+
+fn relative_key(profile_dir: &Path, path: &Path) -> AppResult<String> {
+    let relative = path
+        .strip_prefix(profile_dir)
+        .map_err(|e| AppError::other(e.to_string()))?;
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    Ok(parts.join("/"))
+}
+
+pub fn verify_profile(profile_dir: &Path) -> AppResult<IntegrityReport> {
+    let profile = profile_service::read_profile(profile_dir)?;
+    let lockfile = lockfile_service::read_lockfile(profile_dir)?;
+    let mut report = IntegrityReport::default();
+    let mut accounted = HashSet::new();
+
+    for entry in &profile.mods {
+        let locked = lockfile.mods.get(&entry.mod_id);
+        let Some(file) = installed_file(entry.file.as_deref(), locked) else {
+            report.unverified.push(entry.mod_id.clone());
+            continue;
+        };
+        accounted.insert(file.to_lowercase());
+
+        let issue = IntegrityIssue {
+            mod_id: entry.mod_id.clone(),
+            version: entry.version.clone(),
+            file: file.clone(),
+            repairable: locked.is_some(),
+        };
+        let path = lockfile_service::resolve_lock_path(profile_dir, &file)?;
+        if !path.is_file() {
+            report.missing.push(issue);
+            continue;
+        }
+
+        match locked {
+            Some(locked)
+                if fs::metadata(&path)?.len() != locked.size
+                    || sha256_file(&path)? != locked.sha256 =>
+            {
+                report.modified.push(issue)
+            }
+            Some(_) => report.verified.push(entry.mod_id.clone()),
+            None => report.unverified.push(entry.mod_id.clone()),
+        }
+    }
+
+    let listed: HashSet<&str> = profile
+        .mods
+        .iter()
+        .map(|entry| entry.mod_id.as_str())
+        .collect();
+    for (mod_id, locked) in &lockfile.mods {
+        if !listed.contains(mod_id.as_str()) {
+            accounted.insert(locked.file.to_lowercase());
+            report.orphaned.push(mod_id.clone());
+        }
+    }
+
+    for file in walk_files(&profile_dir.join("BepInEx").join("plugins"))? {
+        let is_dll = file
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("dll"));
+        if !is_dll {
+            continue;
+        }
+        let key = relative_key(profile_dir, &file)?;
+        if !accounted.contains(&key.to_lowercase()) {
+            report.extra.push(key);
+        }
+    }
+    report.extra.sort();
+
+    Ok(report)
+}
+
+/// Re-downloads missing and modified mods that have a lock entry and prunes
+/// orphaned lock entries. Mods whose files are gone for good are reported, and
+/// only dropped from the metadata when `remove_unrecoverable` is set. Extra
+/// files are left alone; they are usually custom mods.
+pub async fn repair_profile<R: Runtime>(
+    app: &AppHandle<R>,
+    profile_dir: &Path,
+    remove_unrecoverable: bool,
+) -> AppResult<IntegrityRepairResult> {
+    let report = verify_profile(profile_dir)?;
+    if report.is_healthy() {
+        return Ok(IntegrityRepairResult {
+            reinstalled: Vec::new(),
+            unrecoverable: Vec::new(),
+            removed_from_metadata: Vec::new(),
+            pruned_lock_entries: Vec::new(),
+            failed: Vec::new(),
+            report,
+        });
+    }
+
+    snapshot_service::snapshot_before(app, profile_dir, SnapshotReason::BeforeRepair);
+
+    let lockfile = lockfile_service::read_lockfile(profile_dir)?;
+    let mut reinstalled = Vec::new();
+    let mut failed = Vec::new();
+    let mut unrecoverable = Vec::new();
+
+    for issue in report.missing.iter().chain(&report.modified) {
+        let Some(locked) = lockfile.mods.get(&issue.mod_id) else {
+            // Only missing files can lack a lock entry; modified ones were
+            // detected against it.
+            unrecoverable.push(issue.mod_id.clone());
+            continue;
+        };
+        match lockfile_service::reinstall_locked_mod(app, profile_dir, &issue.mod_id, locked).await
+        {
+            Ok(()) => reinstalled.push(issue.mod_id.clone()),
+            Err(e) => {
+                warn!("Failed to repair {}: {}", issue.mod_id, e);
+                failed.push(issue.mod_id.clone());
+            }
+        }
+    }
+
+    let mut removed_from_metadata = Vec::new();
+    if remove_unrecoverable && !unrecoverable.is_empty() {
+        profile_service::modify_profile_at(profile_dir, |profile| {
+            profile
+                .mods
+                .retain(|entry| !unrecoverable.contains(&entry.mod_id));
+            Ok(())
+        })?;
+        removed_from_metadata = unrecoverable.clone();
+    }
+    lockfile_service::remove_mods(profile_dir, &report.orphaned)?;
+
+    info!(
+        "Repaired {}: {} reinstalled, {} unrecoverable ({} removed), {} lock entries pruned, {} failed",
+        profile_dir.display(),
+        reinstalled.len(),
+        unrecoverable.len(),
+        removed_from_metadata.len(),
+        report.orphaned.len(),
+        failed.len()
+    );
+    Ok(IntegrityRepairResult {
+        reinstalled,
+        unrecoverable,
+        removed_from_metadata,
+        pruned_lock_entries: report.orphaned,
+        failed,
+        report: verify_profile(profile_dir)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::services::lockfile_service::{ModLockContext, record_mod};
+
+    #[test]
+    fn verify_classifies_plugin_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let profile = temp.path();
+        let plugins = profile.join("BepInEx").join("plugins");
+        fs::create_dir_all(&plugins).unwrap();
+        fs::write(
+            profile.join("metadata.json"),
+            r#"{"id":"p","name":"P","path":"","created_at":1,"mods":[
+                {"mod_id":"ok","version":"1.0.0","file":"ok.dll"},
+                {"mod_id":"changed","version":"1.0.0","file":"changed.dll"},
+                {"mod_id":"deleted","version":"1.0.0","file":"deleted.dll"},
+                {"mod_id":"manual","version":"1.0.0","file":"manual.dll"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let context = ModLockContext {
+            profile_path: profile.to_string_lossy().to_string(),
+            version: "1.0.0".to_string(),
+            platform: None,
+            dependencies: Vec::new(),
+        };
+        for mod_id in ["ok", "changed", "gone"] {
+            let path = plugins.join(format!("{mod_id}.dll"));
+            fs::write(&path, mod_id).unwrap();
+            let sha = sha256_file(&path).unwrap();
+            record_mod(mod_id, "https://example.com", &sha, &path, &context).unwrap();
+        }
+        fs::remove_file(plugins.join("gone.dll")).unwrap();
+        fs::write(plugins.join("changed.dll"), "tampered").unwrap();
+        fs::write(plugins.join("manual.dll"), "manual").unwrap();
+        fs::write(plugins.join("Dropped.dll"), "extra").unwrap();
+
+        let report = verify_profile(profile).unwrap();
+        assert_eq!(report.verified, vec!["ok".to_string()]);
+        assert_eq!(report.modified[0].mod_id, "changed");
+        assert_eq!(report.missing[0].mod_id, "deleted");
+        assert!(!report.missing[0].repairable);
+        assert_eq!(report.unverified, vec!["manual".to_string()]);
+        assert_eq!(
+            report.extra,
+            vec!["BepInEx/plugins/Dropped.dll".to_string()]
+        );
+        assert_eq!(report.orphaned, vec!["gone".to_string()]);
+    }
+}
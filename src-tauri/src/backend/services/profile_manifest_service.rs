@@ -0,0 +1,634 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::extraction_policy::{ExtractionPolicy, ExtractionSession};
+use crate::backend::services::http_retry::{RetryPolicy, retry_transient, status_error};
+use crate::backend::services::lockfile_service::{self, ModLockContext};
+use crate::backend::services::mod_cache_service::walk_files;
+use crate::backend::services::mod_dependency_service::ModDependency;
+use crate::backend::services::mod_download_service::{self, ModDownloadRequest};
+use crate::backend::services::profile_zip_service::{build_sanitized_metadata, to_zip_path};
+use crate::backend::state::archive_operations::CancellationToken;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const MANIFEST_FILE_NAME: &str = "starlight-manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+const METADATA_FILE_NAME: &str = "metadata.json";
+const CONFIG_DIR: [&str; 2] = ["BepInEx", "config"];
+const PLUGINS_DIR: [&str; 2] = ["BepInEx", "plugins"];
+const MAX_MANIFEST_BYTES: u64 = 4 * 1024 * 1024;
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+/// Tried in order when the manifest does not name a platform build the API
+/// still offers. Matches `resolveDownloadTarget` in `mod-install-service.ts`.
+const DEFAULT_PLATFORM: (&str, &str) = ("windows", "x86");
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ManifestMod {
+    pub mod_id: String,
+    pub version: String,
+    /// Install path relative to the profile root. `file`, `url` and `sha256`
+    /// are informational only: imports resolve downloads from the mod API.
+    pub file: Option<String>,
+    pub url: Option<String>,
+    pub sha256: Option<String>,
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<ModDependency>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProfileManifest {
+    pub manifest_version: u32,
+    pub mods: Vec<ManifestMod>,
+}
+
+#[derive(Serialize)]
+pub struct ManifestExportResult {
+    pub mod_count: usize,
+    pub config_count: usize,
+    /// Mods without a known version; they are listed but cannot be
+    /// re-downloaded on import.
+    pub unreferenced: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ManifestImportResult {
+    pub metadata_name: Option<String>,
+    pub installed: Vec<String>,
+    pub unresolved: Vec<String>,
+    pub failed: Vec<ManifestModFailure>,
+}
+
+#[derive(Serialize)]
+pub struct ManifestModFailure {
+    pub mod_id: String,
+    pub error: String,
+}
+
+/// Builds the mod reference list from `metadata.json`, filling download
+/// details from the profile lockfile.
+pub fn build_manifest(profile_dir: &Path, sanitized_metadata: &str) -> AppResult<ProfileManifest> {
+    let lockfile = lockfile_service::read_lockfile(profile_dir)?;
+    let metadata: Value = serde_json::from_str(sanitized_metadata)?;
+
+    let mods = metadata
+        .get("mods")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| {
+            let mod_id = entry.get("mod_id")?.as_str()?.to_string();
+            let version = entry
+                .get("version")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let locked = lockfile.mods.get(&mod_id);
+            Some(ManifestMod {
+                version: locked.map_or(version, |locked| locked.version.clone()),
+                file: locked.map(|locked| locked.file.clone()),
+                url: locked.map(|locked| locked.url.clone()),
+                sha256: locked.map(|locked| locked.sha256.clone()),
+                platform: locked.and_then(|locked| locked.platform.clone()),
+                dependencies: locked
+                    .map(|locked| locked.dependencies.clone())
+                    .unwrap_or_default(),
+                mod_id,
+            })
+        })
+        .collect();
+
+    Ok(ProfileManifest {
+        manifest_version: MANIFEST_VERSION,
+        mods,
+    })
+}
+
+/// Writes a manifest-only archive: sanitized metadata, BepInEx configs and a
+/// mod reference list. No plugin, BepInEx or dotnet binaries are included.
+pub fn export_profile_manifest(
+    profile_path: String,
+    destination: String,
+) -> AppResult<ManifestExportResult> {
+    let profile_dir = Path::new(&profile_path);
+    if !profile_dir.is_dir() {
+        return Err(AppError::validation(format!(
+            "Profile directory does not exist: {}",
+            profile_path
+        )));
+    }
+
+    let destination_path = Path::new(&destination);
+    if let Some(parent) = destination_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let sanitized_metadata = build_sanitized_metadata(profile_dir)?;
+    let manifest = build_manifest(profile_dir, &sanitized_metadata)?;
+
+    let mut zip = ZipWriter::new(File::create(destination_path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(METADATA_FILE_NAME, options)?;
+    zip.write_all(sanitized_metadata.as_bytes())?;
+    zip.start_file(MANIFEST_FILE_NAME, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    let config_dir = CONFIG_DIR
+        .iter()
+        .fold(profile_dir.to_path_buf(), |dir, part| dir.join(part));
+    let mut config_count = 0;
+    for file in walk_files(&config_dir)? {
+        let relative = file
+            .strip_prefix(profile_dir)
+            .map_err(|e| AppError::other(e.to_string()))?;
+        zip.start_file(to_zip_path(relative)?, options)?;
+        std::io::copy(&mut File::open(&file)?, &mut zip)?;
+        config_count += 1;
+    }
+
+    zip.finish()?;
+    info!(
+        "Exported profile manifest: {} -> {} ({} mods)",
+        profile_path,
+        destination,
+        manifest.mods.len()
+    );
+
+    Ok(ManifestExportResult {
+        mod_count: manifest.mods.len(),
+        config_count,
+        unreferenced: manifest
+            .mods
+            .iter()
+            .filter(|entry| entry.version.is_empty())
+            .map(|entry| entry.mod_id.clone())
+            .collect(),
+    })
+}
+
+/// Extracts metadata and configs from a manifest archive into `destination`
+/// and returns the parsed manifest. Entries outside `BepInEx/config` are ignored.
+pub fn extract_profile_manifest(
+    zip_path: &str,
+    destination: &str,
+    policy: &ExtractionPolicy,
+) -> AppResult<(ProfileManifest, Option<String>)> {
+    let mut archive = ZipArchive::new(File::open(zip_path)?)?;
+    let destination_path = Path::new(destination);
+
+    let manifest: ProfileManifest = {
+        let entry = archive.by_name(MANIFEST_FILE_NAME).map_err(|_| {
+            AppError::validation(format!("{zip_path} is not a manifest-only profile export"))
+        })?;
+        let mut content = String::new();
+        entry
+            .take(MAX_MANIFEST_BYTES + 1)
+            .read_to_string(&mut content)?;
+        if content.len() as u64 > MAX_MANIFEST_BYTES {
+            return Err(AppError::validation(format!(
+                "{MANIFEST_FILE_NAME} is larger than {MAX_MANIFEST_BYTES} bytes"
+            )));
+        }
+        serde_json::from_str(&content)?
+    };
+    if manifest.manifest_version > MANIFEST_VERSION {
+        return Err(AppError::validation(format!(
+            "Profile manifest version {} is newer than this version of Starlight supports",
+            manifest.manifest_version
+        )));
+    }
+
+    let mut session = ExtractionSession::new(policy, archive.len())?;
+    session.create_dir_all(destination_path)?;
+    let mut metadata_name = None;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(relative) = entry.enclosed_name() else {
+            warn!("Skipping manifest entry {} with unsafe path", i);
+            continue;
+        };
+
+        let is_metadata = relative == Path::new(METADATA_FILE_NAME);
+        if !is_metadata && !is_config_path(&relative) {
+            continue;
+        }
+
+        let out_path = destination_path.join(&relative);
+        session.extract_file(&mut entry, &out_path)?;
+        if is_metadata {
+            metadata_name = serde_json::from_slice::<Value>(&fs::read(&out_path)?)
+                .ok()
+                .and_then(|value| value.get("name")?.as_str().map(str::to_string))
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty());
+        }
+    }
+
+    session.commit();
+    Ok((manifest, metadata_name))
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiVersionInfo {
+    file_name: String,
+    checksum: String,
+    download_url: Option<String>,
+    #[serde(default)]
+    platforms: Vec<ApiPlatformFile>,
+    #[serde(default)]
+    dependencies: Vec<ModDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPlatformFile {
+    platform: String,
+    architecture: String,
+    file_name: Option<String>,
+    checksum: Option<String>,
+    download_url: Option<String>,
+}
+
+/// Download details for one manifest entry, taken from the mod API.
+#[derive(Debug, PartialEq)]
+struct ResolvedMod {
+    url: String,
+    file_name: String,
+    checksum: String,
+    platform: Option<String>,
+}
+
+/// Picks the download matching the manifest's platform, falling back to the
+/// default platform build and then the version's main file.
+fn select_download(
+    api_base_url: &str,
+    entry: &ManifestMod,
+    info: &ApiVersionInfo,
+) -> AppResult<ResolvedMod> {
+    let legacy_path = format!(
+        "/api/v2/mods/{}/versions/{}/file",
+        entry.mod_id, entry.version
+    );
+    let wanted = entry
+        .platform
+        .as_deref()
+        .and_then(|platform| platform.split_once('-'));
+    let platform_file = wanted
+        .and_then(|wanted| {
+            info.platforms
+                .iter()
+                .find(|file| (file.platform.as_str(), file.architecture.as_str()) == wanted)
+        })
+        .or_else(|| {
+            info.platforms.iter().find(|file| {
+                (file.platform.as_str(), file.architecture.as_str()) == DEFAULT_PLATFORM
+            })
+        });
+
+    let resolved = match platform_file {
+        Some(file) => ResolvedMod {
+            url: file.download_url.clone().unwrap_or_else(|| {
+                format!(
+                    "{legacy_path}?platform={}&arch={}",
+                    file.platform, file.architecture
+                )
+            }),
+            file_name: file
+                .file_name
+                .clone()
+                .unwrap_or_else(|| info.file_name.clone()),
+            checksum: file
+                .checksum
+                .clone()
+                .unwrap_or_else(|| info.checksum.clone()),
+            platform: Some(format!("{}-{}", file.platform, file.architecture)),
+        },
+        None => ResolvedMod {
+            url: info.download_url.clone().unwrap_or(legacy_path),
+            file_name: info.file_name.clone(),
+            checksum: info.checksum.clone(),
+            platform: None,
+        },
+    };
+
+    Ok(ResolvedMod {
+        url: absolute_api_url(api_base_url, &resolved.url)?,
+        ..resolved
+    })
+}
+
+fn absolute_api_url(api_base_url: &str, path_or_url: &str) -> AppResult<String> {
+    let url = if path_or_url.starts_with("https://") || path_or_url.starts_with("http://") {
+        path_or_url.to_string()
+    } else if path_or_url.starts_with('/') {
+        format!("{}{}", api_base_url.trim_end_matches('/'), path_or_url)
+    } else {
+        format!("{}/{}", api_base_url.trim_end_matches('/'), path_or_url)
+    };
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(AppError::validation(format!("Invalid mod API URL: {url}")));
+    }
+    Ok(url)
+}
+
+/// Mod ids and versions end up in API paths; anything that could change the
+/// path or query is rejected.
+fn is_api_path_segment(value: &str) -> bool {
+    !value.is_empty()
+        && value != "."
+        && value != ".."
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+}
+
+/// Resolves the install path for `file_name`, which must be a plain file name
+/// so the download always lands directly in `BepInEx/plugins`.
+fn plugin_destination(profile_dir: &Path, file_name: &str) -> AppResult<std::path::PathBuf> {
+    let mut components = Path::new(file_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if !file_name.contains(['/', '\\']) => {
+            Ok(PLUGINS_DIR
+                .iter()
+                .fold(profile_dir.to_path_buf(), |dir, part| dir.join(part))
+                .join(name))
+        }
+        _ => Err(AppError::validation(format!(
+            "Mod file name {file_name:?} is not a plain file name"
+        ))),
+    }
+}
+
+async fn fetch_version_info(
+    client: &reqwest::Client,
+    api_base_url: &str,
+    entry: &ManifestMod,
+) -> AppResult<ApiVersionInfo> {
+    let url = absolute_api_url(
+        api_base_url,
+        &format!(
+            "/api/v2/mods/{}/versions/{}/info",
+            entry.mod_id, entry.version
+        ),
+    )?;
+    retry_transient(
+        &RetryPolicy::default(),
+        || async {
+            let response = client.get(&url).send().await?;
+            if !response.status().is_success() {
+                return Err(status_error(response.status(), response.headers()));
+            }
+            Ok(response.json::<ApiVersionInfo>().await?)
+        },
+        |_| {},
+    )
+    .await
+}
+
+/// Re-downloads every referenced mod through the checksum-verified download
+/// path, recording each one in the new profile's lockfile. Download details are
+/// looked up by mod id and version from the mod API at `api_base_url`; the
+/// URLs, paths and checksums stored in the manifest are never used.
+pub async fn install_manifest_mods<R: Runtime>(
+    app: &AppHandle<R>,
+    api_base_url: &str,
+    mods: &[ManifestMod],
+    destination: &str,
+    metadata_name: Option<String>,
+) -> AppResult<ManifestImportResult> {
+    let profile_dir = Path::new(destination);
+    let mut result = ManifestImportResult {
+        metadata_name,
+        installed: Vec::new(),
+        unresolved: Vec::new(),
+        failed: Vec::new(),
+    };
+    let client = reqwest::Client::builder().timeout(API_TIMEOUT).build()?;
+
+    for entry in mods {
+        if !is_api_path_segment(&entry.mod_id) || !is_api_path_segment(&entry.version) {
+            result.unresolved.push(entry.mod_id.clone());
+            continue;
+        }
+
+        let outcome = async {
+            let info = fetch_version_info(&client, api_base_url, entry).await?;
+            let resolved = select_download(api_base_url, entry, &info)?;
+            let request = ModDownloadRequest {
+                mod_id: entry.mod_id.clone(),
+                url: resolved.url,
+                destination: plugin_destination(profile_dir, &resolved.file_name)?
+                    .to_string_lossy()
+                    .to_string(),
+                expected_checksum: resolved.checksum,
+                lock: Some(ModLockContext {
+                    profile_path: destination.to_string(),
+                    version: entry.version.clone(),
+                    platform: resolved.platform,
+                    dependencies: info.dependencies,
+                }),
+            };
+            mod_download_service::download_mod(
+                app.clone(),
+                request,
+                RetryPolicy::default(),
+                None,
+                &CancellationToken::default(),
+            )
+            .await
+        }
+        .await;
+
+        match outcome {
+            Ok(()) => result.installed.push(entry.mod_id.clone()),
+            Err(e) => {
+                warn!("Failed to install {} from manifest: {}", entry.mod_id, e);
+                result.failed.push(ManifestModFailure {
+                    mod_id: entry.mod_id.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    info!(
+        "Installed {} of {} manifest mods into {}",
+        result.installed.len(),
+        mods.len(),
+        destination
+    );
+    Ok(result)
+}
+
+fn is_config_path(path: &Path) -> bool {
+    let mut components = path.components();
+    CONFIG_DIR.iter().all(|expected| {
+        matches!(
+            components.next(),
+            Some(Component::Normal(part)) if part.eq_ignore_ascii_case(expected)
+        )
+    }) && components.next().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_config_entries_are_extracted() {
+        assert!(is_config_path(Path::new("BepInEx/config/mod.cfg")));
+        assert!(is_config_path(Path::new("bepinex/Config/sub/mod.cfg")));
+        assert!(!is_config_path(Path::new("BepInEx/config")));
+        assert!(!is_config_path(Path::new("BepInEx/plugins/Mod.dll")));
+    }
+
+    fn manifest_mod(platform: Option<&str>) -> ManifestMod {
+        ManifestMod {
+            mod_id: "a".to_string(),
+            version: "1.0.0".to_string(),
+            file: Some("BepInEx/core/BepInEx.dll".to_string()),
+            url: Some("https://attacker.example/a.dll".to_string()),
+            sha256: Some("0".repeat(64)),
+            platform: platform.map(str::to_string),
+            dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn downloads_are_resolved_from_the_api() {
+        let info: ApiVersionInfo = serde_json::from_str(
+            r#"{"file_name":"A.dll","checksum":"main","platforms":[
+                {"platform":"windows","architecture":"x86","checksum":"x86"},
+                {"platform":"windows","architecture":"x64","file_name":"A64.dll","checksum":"x64",
+                 "download_url":"https://cdn.example.com/a64.dll"}]}"#,
+        )
+        .unwrap();
+
+        let resolved = select_download(
+            "https://api.example.com/",
+            &manifest_mod(Some("windows-x64")),
+            &info,
+        )
+        .unwrap();
+        assert_eq!(
+            resolved,
+            ResolvedMod {
+                url: "https://cdn.example.com/a64.dll".to_string(),
+                file_name: "A64.dll".to_string(),
+                checksum: "x64".to_string(),
+                platform: Some("windows-x64".to_string()),
+            }
+        );
+
+        let resolved =
+            select_download("https://api.example.com", &manifest_mod(None), &info).unwrap();
+        assert_eq!(
+            resolved.url,
+            "https://api.example.com/api/v2/mods/a/versions/1.0.0/file?platform=windows&arch=x86"
+        );
+        assert_eq!(resolved.file_name, "A.dll");
+        assert_eq!(resolved.checksum, "x86");
+    }
+
+    #[test]
+    fn untrusted_names_are_rejected() {
+        assert!(is_api_path_segment("my-mod_2"));
+        assert!(is_api_path_segment("1.2.0+build"));
+        assert!(!is_api_path_segment(".."));
+        assert!(!is_api_path_segment("a/../b"));
+        assert!(!is_api_path_segment("1.0?x=1"));
+
+        let profile = Path::new("profile");
+        assert_eq!(
+            plugin_destination(profile, "Mod.dll").unwrap(),
+            profile.join("BepInEx").join("plugins").join("Mod.dll")
+        );
+        assert!(plugin_destination(profile, "../core/BepInEx.dll").is_err());
+        assert!(plugin_destination(profile, "sub\\Mod.dll").is_err());
+        assert!(plugin_destination(profile, "").is_err());
+    }
+
+    #[test]
+    fn manifest_extraction_enforces_policy() {
+        let temp = tempfile::tempdir().unwrap();
+        let zip_path = temp.path().join("export.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(MANIFEST_FILE_NAME, options).unwrap();
+        zip.write_all(br#"{"manifest_version":1,"mods":[]}"#)
+            .unwrap();
+        zip.start_file("BepInEx/config/mod.cfg", options).unwrap();
+        zip.write_all(&[b'a'; 64]).unwrap();
+        zip.start_file("BepInEx/plugins/Mod.dll", options).unwrap();
+        zip.write_all(b"dll").unwrap();
+        zip.finish().unwrap();
+
+        let destination = temp.path().join("profile");
+        let policy = ExtractionPolicy {
+            max_total_bytes: 16,
+            ..ExtractionPolicy::default()
+        };
+        let destination_str = destination.to_string_lossy();
+        assert!(
+            extract_profile_manifest(&zip_path.to_string_lossy(), &destination_str, &policy)
+                .is_err()
+        );
+        assert!(!destination.exists());
+
+        extract_profile_manifest(
+            &zip_path.to_string_lossy(),
+            &destination_str,
+            &ExtractionPolicy::default(),
+        )
+        .unwrap();
+        assert!(destination.join("BepInEx/config/mod.cfg").is_file());
+        assert!(!destination.join("BepInEx/plugins").exists());
+    }
+
+    #[test]
+    fn manifest_references_locked_mods() {
+        let temp = tempfile::tempdir().unwrap();
+        let profile = temp.path().to_path_buf();
+        let plugins = profile.join("BepInEx").join("plugins");
+        fs::create_dir_all(&plugins).unwrap();
+        fs::write(
+            profile.join(METADATA_FILE_NAME),
+            r#"{"id":"x","name":"P","mods":[{"mod_id":"a","version":"1.0.0"},{"mod_id":"b","version":"2.0.0"}]}"#,
+        )
+        .unwrap();
+
+        let plugin = plugins.join("a.dll");
+        fs::write(&plugin, "a").unwrap();
+        let sha = crate::backend::services::mod_cache_service::sha256_file(&plugin).unwrap();
+        let context = ModLockContext {
+            profile_path: profile.to_string_lossy().to_string(),
+            version: "1.0.0".to_string(),
+            platform: None,
+            dependencies: Vec::new(),
+        };
+        lockfile_service::record_mod("a", "https://example.com/a", &sha, &plugin, &context)
+            .unwrap();
+
+        let metadata = build_sanitized_metadata(&profile).unwrap();
+        let manifest = build_manifest(&profile, &metadata).unwrap();
+        assert_eq!(manifest.mods.len(), 2);
+        assert_eq!(manifest.mods[0].sha256.as_deref(), Some(sha.as_str()));
+        assert_eq!(
+            manifest.mods[0].file.as_deref(),
+            Some("BepInEx/plugins/a.dll")
+        );
+        assert!(manifest.mods[1].url.is_none());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::backend::error::{AppError, AppResult};
+use serde_json::{Map, Value};
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+pub const CURRENT_SCHEMA_VERSION: u64 = 1;
+
+type Migration = fn(&mut Map<String, Value>) -> AppResult<()>;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`. Files
+/// without a `schema_version` are version 0.
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+pub fn schema_version(metadata: &Map<String, Value>) -> AppResult<u64> {
+    match metadata.get(SCHEMA_VERSION_KEY) {
+        None => Ok(0),
+        Some(value) => value
+            .as_u64()
+            .ok_or_else(|| AppError::validation(format!("Invalid {SCHEMA_VERSION_KEY}: {value}"))),
+    }
+}
+
+/// Fails for metadata written by a newer Starlight, which we must not rewrite.
+pub fn ensure_supported(metadata: &Map<String, Value>) -> AppResult<u64> {
+    let version = schema_version(metadata)?;
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(AppError::validation(format!(
+            "Profile metadata uses schema version {version}, but this version of Starlight only supports up to {CURRENT_SCHEMA_VERSION}. Update Starlight to open this profile."
+        )));
+    }
+    Ok(version)
+}
+
+/// Upgrades `metadata` in place to the current schema. Returns the original
+/// version when a migration ran.
+pub fn upgrade(metadata: &mut Map<String, Value>) -> AppResult<Option<u64>> {
+    let from = ensure_supported(metadata)?;
+    if from == CURRENT_SCHEMA_VERSION {
+        return Ok(None);
+    }
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(metadata)?;
+        metadata.insert(
+            SCHEMA_VERSION_KEY.to_string(),
+            Value::from(version as u64 + 1),
+        );
+    }
+    Ok(Some(from))
+}
+
+/// Pre-versioning files were written by the TypeScript repository, which used
+/// JavaScript numbers for timestamps and durations and could omit `mods`.
+fn migrate_v0_to_v1(metadata: &mut Map<String, Value>) -> AppResult<()> {
+    for key in ["created_at", "last_launched_at", "total_play_time"] {
+        let Some(value) = metadata.get(key) else {
+            continue;
+        };
+        let normalized = match value {
+            Value::Number(number) if number.is_u64() => continue,
+            Value::Number(number) => number
+                .as_f64()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .map(|value| Value::from(value.round() as u64)),
+            _ => None,
+        };
+        match normalized {
+            Some(value) => {
+                metadata.insert(key.to_string(), value);
+            }
+            None if key == "created_at" => {
+                metadata.insert(key.to_string(), Value::from(0));
+            }
+            None => {
+                metadata.remove(key);
+            }
+        }
+    }
+
+    if !matches!(metadata.get("mods"), Some(Value::Array(_))) {
+        metadata.insert("mods".to_string(), Value::Array(Vec::new()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn unversioned_metadata_is_upgraded() {
+        let mut metadata = object(serde_json::json!({
+            "name": "Legacy",
+            "created_at": 1700000000000.0,
+            "total_play_time": -5,
+            "last_launched_at": null
+        }));
+
+        assert_eq!(upgrade(&mut metadata).unwrap(), Some(0));
+        assert_eq!(metadata[SCHEMA_VERSION_KEY], CURRENT_SCHEMA_VERSION);
+        assert_eq!(metadata["created_at"], 1700000000000u64);
+        assert!(!metadata.contains_key("total_play_time"));
+        assert!(!metadata.contains_key("last_launched_at"));
+        assert_eq!(metadata["mods"], Value::Array(Vec::new()));
+
+        assert_eq!(upgrade(&mut metadata).unwrap(), None);
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let mut metadata = object(serde_json::json!({
+            "schema_version": CURRENT_SCHEMA_VERSION + 1
+        }));
+        assert!(upgrade(&mut metadata).is_err());
+    }
+}
@@ -0,0 +1,619 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::http_download::sibling_path;
+use crate::backend::services::profile_clone_service::build_profile_id;
+use crate::backend::services::profile_migrations::{self, CURRENT_SCHEMA_VERSION};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+pub const METADATA_FILE_NAME: &str = "metadata.json";
+const MAX_NAME_LENGTH: usize = 100;
+const LEGACY_STORE_NAME: &str = "registry.json";
+const LEGACY_PROFILES_KEY: &str = "profiles";
+
+static LEGACY_MIGRATION_DONE: AtomicBool = AtomicBool::new(false);
+
+/// Serialises read-modify-write cycles of `metadata.json`, so play time
+/// recorded when a game exits and edits sent by the frontend cannot overwrite
+/// each other.
+static METADATA_LOCK: Mutex<()> = Mutex::new(());
+
+/// Mirrors `ProfileModEntry` in `src/lib/features/profiles/schema.ts`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ProfileMod {
+    pub mod_id: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileIconMode {
+    Default,
+    Custom,
+    Mod,
+}
+
+/// Mirrors `ProfileEntry` in `src/lib/features/profiles/schema.ts`. Keys this
+/// struct does not know about are kept in `extra` so they survive a rewrite.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub schema_version: u64,
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_launched_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bepinex_installed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_play_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_mode: Option<ProfileIconMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_icon_extension: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_mod_id: Option<String>,
+    #[serde(default)]
+    pub mods: Vec<ProfileMod>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Profile {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_name(&self.name)?;
+        if self.id.trim().is_empty() {
+            return Err(AppError::validation("Profile id cannot be empty"));
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::validation("Profile name cannot be empty"));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::validation(format!(
+            "Profile name cannot be longer than {MAX_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Only plain directory names are accepted as ids, so an id can never point
+/// outside the profiles directory.
+fn validate_id(id: &str) -> AppResult<()> {
+    let mut components = Path::new(id).components();
+    let is_single_component = matches!(components.next(), Some(std::path::Component::Normal(_)))
+        && components.next().is_none();
+    if id.is_empty() || !is_single_component {
+        return Err(AppError::validation(format!("Invalid profile id: {id}")));
+    }
+    Ok(())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Reads and validates a profile's metadata, upgrading it to the current
+/// schema. The pre-migration file is kept next to it as a backup. `path`
+/// always reflects where the profile actually lives, whatever the file says.
+pub fn read_profile(profile_dir: &Path) -> AppResult<Profile> {
+    let (profile, migrated_from) = load_profile(profile_dir)?;
+    if migrated_from.is_none() {
+        return Ok(profile);
+    }
+    // Re-read under the lock, so the migrated copy cannot overwrite an edit
+    // written since the first read.
+    let _guard = lock_metadata();
+    read_profile_locked(profile_dir)
+}
+
+/// [`read_profile`] for callers that already hold the metadata lock.
+fn read_profile_locked(profile_dir: &Path) -> AppResult<Profile> {
+    let metadata_path = profile_dir.join(METADATA_FILE_NAME);
+    let (profile, migrated_from) = load_profile(profile_dir)?;
+    if let Some((version, content)) = migrated_from {
+        let backup = sibling_path(&metadata_path, &format!("v{version}.bak"));
+        if !backup.exists() {
+            fs::write(&backup, &content)?;
+        }
+        write_profile(&profile)?;
+        info!(
+            "Migrated {} from schema version {} to {}",
+            metadata_path.display(),
+            version,
+            CURRENT_SCHEMA_VERSION
+        );
+    }
+    Ok(profile)
+}
+
+/// Parses a profile's metadata and upgrades it in memory only. When it was
+/// migrated, the original schema version and file content are returned too.
+fn load_profile(profile_dir: &Path) -> AppResult<(Profile, Option<(u64, String)>)> {
+    let metadata_path = profile_dir.join(METADATA_FILE_NAME);
+    let content = fs::read_to_string(&metadata_path)?;
+    let Value::Object(mut metadata) = serde_json::from_str(&content)? else {
+        return Err(AppError::validation(format!(
+            "{} must contain a JSON object",
+            metadata_path.display()
+        )));
+    };
+
+    let migrated_from = profile_migrations::upgrade(&mut metadata)?;
+    let mut profile: Profile = serde_json::from_value(Value::Object(metadata))?;
+    profile.path = profile_dir.to_string_lossy().to_string();
+    profile.validate()?;
+    Ok((profile, migrated_from.map(|version| (version, content))))
+}
+
+pub fn write_profile(profile: &Profile) -> AppResult<()> {
+    profile.validate()?;
+    let path = Path::new(&profile.path).join(METADATA_FILE_NAME);
+    let staging = sibling_path(&path, "tmp");
+    fs::write(&staging, serde_json::to_string_pretty(profile)?)?;
+    fs::rename(&staging, &path)?;
+    Ok(())
+}
+
+fn profile_dirs(profiles_dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let entries = match fs::read_dir(profiles_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
+/// Most recently launched first, then newest.
+pub fn list_profiles(profiles_dir: &Path) -> AppResult<Vec<Profile>> {
+    let mut profiles = Vec::new();
+    for profile_dir in profile_dirs(profiles_dir)? {
+        match read_profile(&profile_dir) {
+            Ok(profile) => profiles.push(profile),
+            Err(e) => warn!("Skipping profile {:?}: {}", profile_dir, e),
+        }
+    }
+
+    profiles.sort_by(|a, b| {
+        b.last_launched_at
+            .unwrap_or(0)
+            .cmp(&a.last_launched_at.unwrap_or(0))
+            .then(b.created_at.cmp(&a.created_at))
+    });
+    Ok(profiles)
+}
+
+pub fn get_profile(profiles_dir: &Path, id: &str) -> AppResult<Profile> {
+    read_profile(&existing_profile_dir(profiles_dir, id)?)
+}
+
+fn existing_profile_dir(profiles_dir: &Path, id: &str) -> AppResult<PathBuf> {
+    validate_id(id)?;
+    let profile_dir = profiles_dir.join(id);
+    if !profile_dir.join(METADATA_FILE_NAME).is_file() {
+        return Err(AppError::validation(format!("Profile '{id}' not found")));
+    }
+    Ok(profile_dir)
+}
+
+/// Only reads metadata, so it is safe to call while holding the metadata lock.
+fn ensure_unique_name(profiles_dir: &Path, name: &str, except_id: Option<&str>) -> AppResult<()> {
+    let taken = profile_dirs(profiles_dir)?
+        .iter()
+        .filter_map(|profile_dir| load_profile(profile_dir).ok())
+        .any(|(profile, _)| {
+            Some(profile.id.as_str()) != except_id
+                && profile.name.to_lowercase() == name.to_lowercase()
+        });
+    if taken {
+        return Err(AppError::validation(format!(
+            "Profile '{name}' already exists"
+        )));
+    }
+    Ok(())
+}
+
+pub fn create_profile(profiles_dir: &Path, name: &str) -> AppResult<Profile> {
+    let name = name.trim();
+    validate_name(name)?;
+    let _guard = lock_metadata();
+    ensure_unique_name(profiles_dir, name, None)?;
+
+    let created_at = now_millis();
+    let id = build_profile_id(name, created_at);
+    let profile_dir = profiles_dir.join(&id);
+    if profile_dir.exists() {
+        return Err(AppError::validation(format!(
+            "Profile directory already exists: {}",
+            profile_dir.display()
+        )));
+    }
+    fs::create_dir_all(&profile_dir)?;
+
+    let profile = Profile {
+        schema_version: CURRENT_SCHEMA_VERSION,
+        id,
+        name: name.to_string(),
+        path: profile_dir.to_string_lossy().to_string(),
+        created_at,
+        last_launched_at: None,
+        bepinex_installed: Some(false),
+        total_play_time: Some(0),
+        icon_mode: Some(ProfileIconMode::Default),
+        custom_icon_extension: None,
+        icon_mod_id: None,
+        mods: Vec::new(),
+        extra: Map::new(),
+    };
+    if let Err(e) = write_profile(&profile) {
+        fs::remove_dir_all(&profile_dir).ok();
+        return Err(e);
+    }
+
+    info!("Created profile {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+fn lock_metadata() -> MutexGuard<'static, ()> {
+    METADATA_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Reads a profile, applies `modify` and writes it back while holding the
+/// metadata lock.
+pub fn modify_profile(
+    profiles_dir: &Path,
+    id: &str,
+    modify: impl FnOnce(&mut Profile) -> AppResult<()>,
+) -> AppResult<Profile> {
+    validate_id(id)?;
+    modify_profile_at(&profiles_dir.join(id), modify)
+}
+
+/// [`modify_profile`] for a profile given by its directory.
+pub fn modify_profile_at(
+    profile_dir: &Path,
+    modify: impl FnOnce(&mut Profile) -> AppResult<()>,
+) -> AppResult<Profile> {
+    let _guard = lock_metadata();
+    let mut profile = read_profile_locked(profile_dir)?;
+    modify(&mut profile)?;
+    write_profile(&profile)?;
+    Ok(profile)
+}
+
+/// Replaces the stored metadata of an existing profile. The id and directory
+/// cannot be changed this way, and `last_launched_at` and `total_play_time`
+/// are kept: only the game runtime records those.
+pub fn update_profile(profiles_dir: &Path, mut profile: Profile) -> AppResult<Profile> {
+    let _guard = lock_metadata();
+    let existing = read_profile_locked(&existing_profile_dir(profiles_dir, &profile.id)?)?;
+    profile.name = profile.name.trim().to_string();
+    if profile.name != existing.name {
+        ensure_unique_name(profiles_dir, &profile.name, Some(&profile.id))?;
+    }
+    profile.path = existing.path;
+    profile.last_launched_at = existing.last_launched_at;
+    profile.total_play_time = existing.total_play_time;
+    profile.schema_version = CURRENT_SCHEMA_VERSION;
+    write_profile(&profile)?;
+    Ok(profile)
+}
+
+/// Registers a profile whose directory was just filled from an imported
+/// archive. Metadata from a newer schema is refused, the play time is reset and
+/// `bepinex_installed` reflects what the archive actually contained.
+pub fn register_imported_profile(profiles_dir: &Path, mut profile: Profile) -> AppResult<Profile> {
+    validate_id(&profile.id)?;
+    let profile_dir = profiles_dir.join(&profile.id);
+    if !profile_dir.is_dir() {
+        return Err(AppError::validation(format!(
+            "Profile '{}' not found",
+            profile.id
+        )));
+    }
+
+    let metadata_path = profile_dir.join(METADATA_FILE_NAME);
+    if metadata_path.is_file()
+        && let Value::Object(metadata) = serde_json::from_str(&fs::read_to_string(&metadata_path)?)?
+    {
+        profile_migrations::ensure_supported(&metadata)?;
+    }
+
+    profile.name = profile.name.trim().to_string();
+    validate_name(&profile.name)?;
+    let path = profile_dir.to_string_lossy().to_string();
+    let taken = list_profiles(profiles_dir)?.into_iter().any(|existing| {
+        existing.path != path && existing.name.to_lowercase() == profile.name.to_lowercase()
+    });
+    if taken {
+        return Err(AppError::validation(format!(
+            "Profile '{}' already exists",
+            profile.name
+        )));
+    }
+
+    profile.schema_version = CURRENT_SCHEMA_VERSION;
+    profile.path = path;
+    profile.total_play_time = Some(0);
+    profile.bepinex_installed = Some(profile_dir.join("BepInEx").join("core").is_dir());
+    write_profile(&profile)?;
+    info!(
+        "Registered imported profile {} ({})",
+        profile.name, profile.id
+    );
+    Ok(profile)
+}
+
+pub fn rename_profile(profiles_dir: &Path, id: &str, name: &str) -> AppResult<Profile> {
+    let mut profile = get_profile(profiles_dir, id)?;
+    profile.name = name.to_string();
+    update_profile(profiles_dir, profile)
+}
+
+pub fn delete_profile(profiles_dir: &Path, id: &str) -> AppResult<()> {
+    validate_id(id)?;
+    let profile_dir = profiles_dir.join(id);
+    let _guard = lock_metadata();
+    if !profile_dir.is_dir() {
+        return Err(AppError::validation(format!("Profile '{id}' not found")));
+    }
+    fs::remove_dir_all(&profile_dir)?;
+    info!("Deleted profile {}", id);
+    Ok(())
+}
+
+/// Writes `metadata.json` for legacy registry entries whose directory exists
+/// but has no readable metadata yet. Returns the ids that are now backed by a
+/// metadata file, including ones that already were.
+pub fn migrate_legacy_profiles(profiles_dir: &Path, legacy: &[Value]) -> AppResult<Vec<String>> {
+    let mut settled = Vec::new();
+    for entry in legacy {
+        let Value::Object(mut metadata) = entry.clone() else {
+            continue;
+        };
+        let parsed = profile_migrations::upgrade(&mut metadata)
+            .and_then(|_| Ok(serde_json::from_value::<Profile>(Value::Object(metadata))?));
+        let mut profile = match parsed {
+            Ok(profile) => profile,
+            Err(e) => {
+                warn!("Skipping invalid legacy profile entry: {}", e);
+                continue;
+            }
+        };
+        if validate_id(&profile.id).is_err() {
+            continue;
+        }
+
+        let profile_dir = profiles_dir.join(&profile.id);
+        if !profile_dir.is_dir() {
+            continue;
+        }
+        if read_profile(&profile_dir).is_err() {
+            profile.path = profile_dir.to_string_lossy().to_string();
+            write_profile(&profile)?;
+            info!("Migrated profile {} to {}", profile.id, METADATA_FILE_NAME);
+        }
+        settled.push(profile.id);
+    }
+    Ok(settled)
+}
+
+/// Moves profiles from the legacy `registry.json` store into per-profile
+/// metadata files. Runs once per process; the legacy key is cleared when every
+/// entry has been migrated.
+pub fn migrate_legacy_registry<R: Runtime>(
+    app: &AppHandle<R>,
+    profiles_dir: &Path,
+) -> AppResult<()> {
+    if LEGACY_MIGRATION_DONE.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let store = app
+        .store(LEGACY_STORE_NAME)
+        .map_err(|e| AppError::state(format!("Failed to load registry store: {e}")))?;
+    let legacy = match store.get(LEGACY_PROFILES_KEY) {
+        Some(Value::Array(entries)) if !entries.is_empty() => entries,
+        _ => {
+            LEGACY_MIGRATION_DONE.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+    };
+
+    let settled = migrate_legacy_profiles(profiles_dir, &legacy)?;
+    let mut remaining: HashSet<&str> = legacy
+        .iter()
+        .filter_map(|entry| entry.get("id").and_then(Value::as_str))
+        .collect();
+    for id in &settled {
+        remaining.remove(id.as_str());
+    }
+
+    if remaining.is_empty() {
+        store.set(LEGACY_PROFILES_KEY, Value::Array(Vec::new()));
+        store
+            .save()
+            .map_err(|e| AppError::state(format!("Failed to save registry store: {e}")))?;
+        info!("All legacy profiles migrated; cleared registry entry");
+    } else {
+        debug!(
+            "{} legacy profiles could not be migrated yet",
+            remaining.len()
+        );
+    }
+    LEGACY_MIGRATION_DONE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_round_trip_and_keep_unknown_keys() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let created = create_profile(root, "  Town of Us ").unwrap();
+        assert_eq!(created.name, "Town of Us");
+        assert!(create_profile(root, "town of us").is_err());
+
+        let mut profile = get_profile(root, &created.id).unwrap();
+        profile.mods.push(ProfileMod {
+            mod_id: "tou".to_string(),
+            version: "5.1.0".to_string(),
+            file: Some("TownOfUs.dll".to_string()),
+        });
+        profile
+            .extra
+            .insert("future_field".to_string(), Value::from(1));
+        profile.total_play_time = Some(1_000);
+        update_profile(root, profile).unwrap();
+        modify_profile(root, &created.id, |profile| {
+            profile.total_play_time = Some(500);
+            Ok(())
+        })
+        .unwrap();
+
+        let renamed = rename_profile(root, &created.id, "Renamed").unwrap();
+        let reread = get_profile(root, &created.id).unwrap();
+        assert_eq!(reread.name, "Renamed");
+        assert_eq!(reread.mods, renamed.mods);
+        assert_eq!(reread.extra["future_field"], 1);
+        assert_eq!(reread.total_play_time, Some(500));
+
+        assert_eq!(list_profiles(root).unwrap().len(), 1);
+        delete_profile(root, &created.id).unwrap();
+        assert!(list_profiles(root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn legacy_entries_are_written_to_metadata() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("legacy-1")).unwrap();
+        let legacy = vec![
+            serde_json::json!({
+                "id": "legacy-1",
+                "name": "Legacy",
+                "path": "/somewhere/else",
+                "created_at": 1,
+                "mods": []
+            }),
+            serde_json::json!({ "id": "broken" }),
+        ];
+
+        let settled = migrate_legacy_profiles(root, &legacy).unwrap();
+        assert_eq!(settled, vec!["legacy-1".to_string()]);
+        let profile = get_profile(root, "legacy-1").unwrap();
+        assert_eq!(profile.path, root.join("legacy-1").to_string_lossy());
+    }
+
+    #[test]
+    fn unversioned_metadata_is_migrated_with_backup() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let profile_dir = root.join("old-1");
+        fs::create_dir_all(&profile_dir).unwrap();
+        let original = r#"{"id":"old-1","name":"Old","path":"","created_at":1.0,"mods":[]}"#;
+        fs::write(profile_dir.join(METADATA_FILE_NAME), original).unwrap();
+
+        let profile = get_profile(root, "old-1").unwrap();
+        assert_eq!(profile.schema_version, CURRENT_SCHEMA_VERSION);
+        let backup = fs::read_to_string(profile_dir.join("metadata.json.v0.bak")).unwrap();
+        assert_eq!(backup, original);
+
+        let newer = format!(
+            r#"{{"schema_version":{},"id":"old-1","name":"Old","path":"","created_at":1,"mods":[]}}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        fs::write(profile_dir.join(METADATA_FILE_NAME), &newer).unwrap();
+        assert!(get_profile(root, "old-1").is_err());
+        assert_eq!(
+            fs::read_to_string(profile_dir.join(METADATA_FILE_NAME)).unwrap(),
+            newer
+        );
+    }
+
+    #[test]
+    fn imported_profiles_are_registered_at_the_current_schema() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        create_profile(root, "Existing").unwrap();
+        let profile_dir = root.join("imported-1");
+        fs::create_dir_all(profile_dir.join("BepInEx").join("core")).unwrap();
+        fs::write(
+            profile_dir.join(METADATA_FILE_NAME),
+            r#"{"id":"original","name":"Existing","path":"","created_at":1,"total_play_time":9}"#,
+        )
+        .unwrap();
+        let imported = |name: &str| Profile {
+            schema_version: 0,
+            id: "imported-1".to_string(),
+            name: name.to_string(),
+            path: String::new(),
+            created_at: 2,
+            last_launched_at: None,
+            bepinex_installed: None,
+            total_play_time: Some(9),
+            icon_mode: None,
+            custom_icon_extension: None,
+            icon_mod_id: None,
+            mods: Vec::new(),
+            extra: Map::new(),
+        };
+
+        assert!(register_imported_profile(root, imported("existing")).is_err());
+        let registered = register_imported_profile(root, imported("Existing (2)")).unwrap();
+        assert_eq!(registered.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(registered.bepinex_installed, Some(true));
+        assert_eq!(registered.total_play_time, Some(0));
+        let reread = get_profile(root, "imported-1").unwrap();
+        assert_eq!(reread.name, "Existing (2)");
+
+        fs::write(
+            profile_dir.join(METADATA_FILE_NAME),
+            format!(
+                r#"{{"schema_version":{},"id":"x","name":"New","path":"","created_at":1}}"#,
+                CURRENT_SCHEMA_VERSION + 1
+            ),
+        )
+        .unwrap();
+        assert!(register_imported_profile(root, imported("Newer")).is_err());
+    }
+
+    #[test]
+    fn ids_cannot_escape_profiles_dir() {
+        assert!(validate_id("../other").is_err());
+        assert!(validate_id("a/b").is_err());
+        assert!(validate_id("my-profile-1").is_ok());
+    }
+}
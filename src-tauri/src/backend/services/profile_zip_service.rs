@@ -1,19 +1,213 @@
 use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::extraction_policy::{ExtractionPolicy, ExtractionSession};
+use crate::backend::services::http_download::sibling_path;
+use crate::backend::services::play_time_service::SESSION_LOG_FILE_NAME;
+use crate::backend::services::profile_migrations;
+use crate::backend::services::r2modman_service::{self, R2modmanExport, ReferencedPackage};
+use crate::backend::state::archive_operations::{ArchiveProgress, CancellationToken};
 use log::{info, warn};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Component, Path, PathBuf};
+use zip::read::ZipFile;
 use zip::write::SimpleFileOptions;
 use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
+const MAX_METADATA_BYTES: u64 = 1024 * 1024;
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+const PROGRESS_INTERVAL_BYTES: u64 = 1024 * 1024;
+const STAGING_SUFFIX: &str = "importing";
+const REPLACED_SUFFIX: &str = "replaced";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileArchiveFormat {
+    Starlight,
+    R2modman,
+}
+
 #[derive(Serialize)]
 pub struct ProfileImportResult {
     pub metadata_name: Option<String>,
+    pub format: ProfileArchiveFormat,
+    /// Thunderstore packages listed by an r2modman export; empty for
+    /// Starlight archives.
+    pub referenced_packages: Vec<ReferencedPackage>,
+}
+
+struct ExtractedArchive {
+    format: ProfileArchiveFormat,
+    metadata_name: Option<String>,
+    referenced_packages: Vec<ReferencedPackage>,
+}
+
+#[derive(Serialize)]
+pub struct ProfileZipMod {
+    pub mod_id: String,
+    pub version: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProfileZipInspection {
+    pub format: ProfileArchiveFormat,
+    pub root_prefix: Option<String>,
+    pub file_count: usize,
+    pub total_uncompressed_size: u64,
+    pub metadata_name: Option<String>,
+    pub mods: Vec<ProfileZipMod>,
+    pub unsafe_entries: Vec<String>,
+    /// Symbolic link entries; importing an archive that has any fails.
+    pub symlink_entries: Vec<String>,
+    pub includes_bepinex: bool,
+    pub includes_dotnet: bool,
+    /// Thunderstore packages listed by an r2modman export.
+    pub referenced_packages: Vec<ReferencedPackage>,
+}
+
+/// How an archive maps onto a profile directory. Import and inspection both
+/// classify entries through this so they agree on what gets extracted.
+struct ArchiveLayout {
+    r2modman_export: Option<R2modmanExport>,
+    root_prefix: Option<String>,
+}
+
+enum ArchiveEntryKind {
+    /// The entry's path escapes the destination.
+    Unsafe,
+    Symlink,
+    Directory(PathBuf),
+    File(PathBuf),
+    /// Not part of the profile, e.g. non-config files in an r2modman export.
+    Ignored,
+}
+
+impl ArchiveLayout {
+    fn read(archive: &mut ZipArchive<File>) -> AppResult<Self> {
+        let r2modman_export = read_r2modman_export(archive)?;
+        let root_prefix = match r2modman_export {
+            Some(_) => None,
+            None => detect_common_root_prefix(archive)?,
+        };
+        Ok(Self {
+            r2modman_export,
+            root_prefix,
+        })
+    }
+
+    fn format(&self) -> ProfileArchiveFormat {
+        match self.r2modman_export {
+            Some(_) => ProfileArchiveFormat::R2modman,
+            None => ProfileArchiveFormat::Starlight,
+        }
+    }
+
+    fn classify(&self, entry: &ZipFile) -> ArchiveEntryKind {
+        let Some(raw_entry_path) = entry.enclosed_name() else {
+            return ArchiveEntryKind::Unsafe;
+        };
+
+        let relative_path = if self.r2modman_export.is_some() {
+            match r2modman_service::map_entry_path(&raw_entry_path) {
+                Some(path) => path,
+                None => return ArchiveEntryKind::Ignored,
+            }
+        } else {
+            strip_root_prefix(&raw_entry_path, self.root_prefix.as_deref())
+        };
+
+        if relative_path.as_os_str().is_empty() || is_metadata_leftover(&relative_path) {
+            ArchiveEntryKind::Ignored
+        } else if entry.is_symlink() {
+            ArchiveEntryKind::Symlink
+        } else if entry.is_dir() {
+            ArchiveEntryKind::Directory(relative_path)
+        } else {
+            ArchiveEntryKind::File(relative_path)
+        }
+    }
+}
+
+/// Reports archive progress and checks for cancellation between chunks.
+pub struct ArchiveTracker<'a> {
+    progress: ArchiveProgress,
+    cancel: &'a CancellationToken,
+    on_progress: &'a mut dyn FnMut(&ArchiveProgress),
+    last_emitted_bytes: u64,
+}
+
+impl<'a> ArchiveTracker<'a> {
+    pub fn new(
+        cancel: &'a CancellationToken,
+        on_progress: &'a mut dyn FnMut(&ArchiveProgress),
+    ) -> Self {
+        Self {
+            progress: ArchiveProgress::default(),
+            cancel,
+            on_progress,
+            last_emitted_bytes: 0,
+        }
+    }
+
+    fn emit(&mut self) {
+        self.last_emitted_bytes = self.progress.bytes_processed;
+        (self.on_progress)(&self.progress);
+    }
+
+    fn stage(&mut self, stage: &str) -> AppResult<()> {
+        self.cancel.check()?;
+        self.progress.stage = stage.to_string();
+        self.progress.current_file = None;
+        self.emit();
+        Ok(())
+    }
+
+    /// Reports completion; the operation can no longer be cancelled.
+    fn complete(&mut self) {
+        self.progress.stage = "complete".to_string();
+        self.progress.current_file = None;
+        self.emit();
+    }
+
+    fn set_totals(&mut self, total_bytes: u64, total_entries: usize) {
+        self.progress.total_bytes = total_bytes;
+        self.progress.total_entries = total_entries;
+    }
+
+    fn start_entry(&mut self, name: &str) -> AppResult<()> {
+        self.cancel.check()?;
+        self.progress.current_file = Some(name.to_string());
+        self.emit();
+        Ok(())
+    }
+
+    fn add_bytes(&mut self, bytes: u64) -> AppResult<()> {
+        self.cancel.check()?;
+        self.progress.bytes_processed += bytes;
+        if self.progress.bytes_processed - self.last_emitted_bytes >= PROGRESS_INTERVAL_BYTES {
+            self.emit();
+        }
+        Ok(())
+    }
+
+    fn finish_entry(&mut self) {
+        self.progress.entries_processed += 1;
+    }
+}
+
+struct ExportEntry {
+    path: PathBuf,
+    zip_path: String,
+    is_dir: bool,
+    size: u64,
 }
 
-pub fn export_profile_zip(profile_path: String, destination: String) -> AppResult<()> {
+pub fn export_profile_zip(
+    profile_path: String,
+    destination: String,
+    tracker: &mut ArchiveTracker,
+) -> AppResult<()> {
     let profile_dir = Path::new(&profile_path);
     if !profile_dir.exists() || !profile_dir.is_dir() {
         return Err(AppError::validation(format!(
@@ -27,103 +221,420 @@ pub fn export_profile_zip(profile_path: String, destination: String) -> AppResul
         fs::create_dir_all(parent)?;
     }
 
+    if let Err(e) = write_profile_zip(profile_dir, destination_path, tracker) {
+        fs::remove_file(destination_path).ok();
+        return Err(e);
+    }
+
+    tracker.complete();
+    info!("Exported profile zip: {} -> {}", profile_path, destination);
+    Ok(())
+}
+
+fn write_profile_zip(
+    profile_dir: &Path,
+    destination_path: &Path,
+    tracker: &mut ArchiveTracker,
+) -> AppResult<()> {
+    tracker.stage("scanning")?;
     let sanitized_metadata = build_sanitized_metadata(profile_dir)?;
+    let mut entries = Vec::new();
+    collect_export_entries(profile_dir, profile_dir, destination_path, &mut entries)?;
+    tracker.set_totals(entries.iter().map(|entry| entry.size).sum(), entries.len());
 
     let output = File::create(destination_path)?;
     let mut zip = ZipWriter::new(output);
     let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
     let mut metadata_written = false;
 
-    add_directory_to_zip(
-        &mut zip,
-        profile_dir,
-        profile_dir,
-        destination_path,
-        options,
-        &sanitized_metadata,
-        &mut metadata_written,
-    )?;
+    tracker.stage("writing")?;
+    for entry in &entries {
+        tracker.start_entry(&entry.zip_path)?;
+        if entry.is_dir {
+            zip.add_directory(format!("{}/", entry.zip_path), options)?;
+        } else if is_metadata_file(Path::new(&entry.zip_path)) {
+            zip.start_file(entry.zip_path.as_str(), options)?;
+            metadata_written = true;
+            zip.write_all(sanitized_metadata.as_bytes())?;
+            tracker.add_bytes(entry.size)?;
+        } else {
+            zip.start_file(entry.zip_path.as_str(), options)?;
+            copy_with_progress(&mut File::open(&entry.path)?, &mut zip, tracker)?;
+        }
+        tracker.finish_entry();
+    }
 
     if !metadata_written {
         zip.start_file("metadata.json", options)?;
         zip.write_all(sanitized_metadata.as_bytes())?;
     }
 
+    tracker.stage("finalizing")?;
     zip.finish()?;
-    info!("Exported profile zip: {} -> {}", profile_path, destination);
     Ok(())
 }
 
-pub fn import_profile_zip(zip_path: String, destination: String) -> AppResult<ProfileImportResult> {
-    let zip_file = File::open(&zip_path)?;
-    let mut archive = ZipArchive::new(zip_file)?;
+fn copy_with_progress(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    tracker: &mut ArchiveTracker,
+) -> AppResult<()> {
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        writer.write_all(&buffer[..read])?;
+        tracker.add_bytes(read as u64)?;
+    }
+}
+
+/// What to do when the import destination already contains files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileImportMode {
+    /// Refuse to import into a non-empty destination.
+    #[default]
+    Create,
+    /// Replace the existing profile directory.
+    Overwrite,
+    /// Copy archive files over the existing profile, keeping other files.
+    Merge,
+}
+
+/// Removes the staging directory on drop unless it was moved into place.
+struct StagingDir {
+    path: PathBuf,
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if self.path.exists()
+            && let Err(e) = fs::remove_dir_all(&self.path)
+        {
+            warn!("Failed to remove import staging dir {:?}: {}", self.path, e);
+        }
+    }
+}
 
+/// Extracts into a sibling staging directory, checks that `metadata.json`
+/// parses, and only then moves the result into `destination`.
+pub fn import_profile_zip(
+    zip_path: String,
+    destination: String,
+    policy: &ExtractionPolicy,
+    mode: ProfileImportMode,
+    tracker: &mut ArchiveTracker,
+) -> AppResult<ProfileImportResult> {
     let destination_path = Path::new(&destination);
-    fs::create_dir_all(destination_path)?;
+    if mode == ProfileImportMode::Create && !is_empty_or_missing(destination_path)? {
+        return Err(AppError::validation(format!(
+            "Import destination is not empty: {}",
+            destination
+        )));
+    }
+
+    let staging = StagingDir {
+        path: sibling_path(destination_path, STAGING_SUFFIX),
+    };
+    if staging.path.exists() {
+        fs::remove_dir_all(&staging.path)?;
+    }
+
+    let extracted = extract_profile_archive(Path::new(&zip_path), &staging.path, policy, tracker)?;
+    tracker.stage("validating")?;
+    validate_staged_metadata(&staging.path)?;
+
+    tracker.stage("finalizing")?;
+
+    match mode {
+        ProfileImportMode::Merge if destination_path.exists() => {
+            merge_directory(&staging.path, destination_path)?;
+        }
+        ProfileImportMode::Overwrite if destination_path.exists() => {
+            replace_directory(&staging.path, destination_path)?;
+        }
+        _ => {
+            if destination_path.exists() {
+                fs::remove_dir(destination_path)?;
+            }
+            fs::rename(&staging.path, destination_path)?;
+        }
+    }
 
-    let root_prefix = detect_common_root_prefix(&mut archive)?;
+    tracker.complete();
+    info!(
+        "Imported {:?} profile zip ({:?}): {} -> {}",
+        extracted.format, mode, zip_path, destination
+    );
+    Ok(ProfileImportResult {
+        metadata_name: extracted
+            .metadata_name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty()),
+        format: extracted.format,
+        referenced_packages: extracted.referenced_packages,
+    })
+}
+
+fn extract_profile_archive(
+    zip_path: &Path,
+    destination_path: &Path,
+    policy: &ExtractionPolicy,
+    tracker: &mut ArchiveTracker,
+) -> AppResult<ExtractedArchive> {
+    let zip_file = File::open(zip_path)?;
+    let mut archive = ZipArchive::new(zip_file)?;
+    let mut session = ExtractionSession::new(policy, archive.len())?;
+    session.create_dir_all(destination_path)?;
+
+    let mut declared_bytes = 0;
+    for i in 0..archive.len() {
+        declared_bytes += archive.by_index_raw(i)?.size();
+    }
+    tracker.set_totals(declared_bytes, archive.len());
+    tracker.stage("extracting")?;
+
+    let layout = ArchiveLayout::read(&mut archive)?;
     let mut metadata_name: Option<String> = None;
 
     for i in 0..archive.len() {
         let mut entry = archive.by_index(i)?;
-        let Some(raw_entry_path) = entry.enclosed_name().map(|p| p.to_path_buf()) else {
-            warn!("Skipping entry {} with unsafe path", i);
-            continue;
+        let relative_path = match layout.classify(&entry) {
+            ArchiveEntryKind::Unsafe => {
+                warn!("Skipping entry {} with unsafe path", i);
+                continue;
+            }
+            ArchiveEntryKind::Ignored => continue,
+            ArchiveEntryKind::Symlink => {
+                return Err(AppError::validation(format!(
+                    "Archive entry {} is a symbolic link",
+                    entry.name()
+                )));
+            }
+            ArchiveEntryKind::Directory(relative_path) => {
+                tracker.start_entry(&relative_path.to_string_lossy())?;
+                session.create_dir_all(&destination_path.join(&relative_path))?;
+                tracker.finish_entry();
+                continue;
+            }
+            ArchiveEntryKind::File(relative_path) => relative_path,
         };
 
-        let relative_path = strip_root_prefix(&raw_entry_path, root_prefix.as_deref());
-        if relative_path.as_os_str().is_empty() {
-            continue;
-        }
-
         let out_path = destination_path.join(&relative_path);
-        if entry.is_dir() {
-            fs::create_dir_all(&out_path)?;
-            continue;
+        tracker.start_entry(&relative_path.to_string_lossy())?;
+        session.extract_file_with(&mut entry, &out_path, |bytes| tracker.add_bytes(bytes))?;
+        tracker.finish_entry();
+        if metadata_name.is_none()
+            && relative_path.components().count() == 1
+            && is_metadata_file(&relative_path)
+        {
+            metadata_name = extract_name_from_metadata(&fs::read(&out_path)?);
         }
 
-        if let Some(parent) = out_path.parent() {
-            fs::create_dir_all(parent)?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&out_path, fs::Permissions::from_mode(mode)).ok();
         }
+    }
 
-        if is_metadata_file(&relative_path) {
-            let mut bytes = Vec::new();
-            entry.read_to_end(&mut bytes)?;
-            fs::write(&out_path, &bytes)?;
-            if metadata_name.is_none() {
-                metadata_name = extract_name_from_metadata(&bytes);
+    let extracted = match layout.r2modman_export {
+        Some(export) => {
+            let mut metadata = Map::new();
+            metadata.insert(
+                profile_migrations::SCHEMA_VERSION_KEY.to_string(),
+                Value::from(profile_migrations::CURRENT_SCHEMA_VERSION),
+            );
+            if let Some(name) = &export.profile_name {
+                metadata.insert("name".to_string(), Value::String(name.clone()));
+            }
+            metadata.insert("mods".to_string(), Value::Array(vec![]));
+            fs::write(
+                destination_path.join("metadata.json"),
+                serde_json::to_string_pretty(&Value::Object(metadata))?,
+            )?;
+
+            ExtractedArchive {
+                format: ProfileArchiveFormat::R2modman,
+                metadata_name: export.profile_name,
+                referenced_packages: export.packages,
             }
+        }
+        None => ExtractedArchive {
+            format: ProfileArchiveFormat::Starlight,
+            metadata_name,
+            referenced_packages: Vec::new(),
+        },
+    };
+
+    session.commit();
+    Ok(extracted)
+}
+
+fn read_r2modman_export(archive: &mut ZipArchive<File>) -> AppResult<Option<R2modmanExport>> {
+    let Ok(entry) = archive.by_name(r2modman_service::EXPORT_FILE_NAME) else {
+        return Ok(None);
+    };
+
+    let mut content = String::new();
+    entry
+        .take(MAX_METADATA_BYTES)
+        .read_to_string(&mut content)?;
+    r2modman_service::parse_export(&content).map(Some)
+}
+
+fn validate_staged_metadata(staging: &Path) -> AppResult<()> {
+    let content = fs::read_to_string(staging.join("metadata.json")).map_err(|_| {
+        AppError::validation("Profile archive does not contain a metadata.json file")
+    })?;
+    match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(metadata)) => profile_migrations::ensure_supported(&metadata).map(|_| ()),
+        Ok(_) => Err(AppError::validation(
+            "Profile metadata.json must contain a JSON object",
+        )),
+        Err(e) => Err(AppError::validation(format!(
+            "Profile metadata.json is not valid JSON: {e}"
+        ))),
+    }
+}
+
+fn is_empty_or_missing(path: &Path) -> AppResult<bool> {
+    match fs::read_dir(path) {
+        Ok(mut entries) => Ok(entries.next().is_none()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Swaps `staging` into `destination`, restoring the previous directory if
+/// the final rename fails.
+fn replace_directory(staging: &Path, destination: &Path) -> AppResult<()> {
+    let backup = sibling_path(destination, REPLACED_SUFFIX);
+    if backup.exists() {
+        fs::remove_dir_all(&backup)?;
+    }
+
+    fs::rename(destination, &backup)?;
+    if let Err(e) = fs::rename(staging, destination) {
+        fs::rename(&backup, destination).ok();
+        return Err(e.into());
+    }
+
+    if let Err(e) = fs::remove_dir_all(&backup) {
+        warn!("Failed to remove replaced profile {:?}: {}", backup, e);
+    }
+    Ok(())
+}
+
+fn merge_directory(source: &Path, destination: &Path) -> AppResult<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            merge_directory(&entry.path(), &target)?;
         } else {
-            let mut output = File::create(&out_path)?;
-            std::io::copy(&mut entry, &mut output)?;
+            if target.is_dir() {
+                fs::remove_dir_all(&target)?;
+            }
+            fs::rename(entry.path(), &target)?;
         }
+    }
+    Ok(())
+}
 
-        #[cfg(unix)]
-        if let Some(mode) = entry.unix_mode() {
-            use std::os::unix::fs::PermissionsExt;
-            fs::set_permissions(&out_path, fs::Permissions::from_mode(mode)).ok();
+/// Reads a profile archive's central directory and metadata without writing
+/// anything to disk.
+pub fn inspect_profile_zip(zip_path: String) -> AppResult<ProfileZipInspection> {
+    let zip_file = File::open(&zip_path)?;
+    let mut archive = ZipArchive::new(zip_file)?;
+    let layout = ArchiveLayout::read(&mut archive)?;
+
+    let mut inspection = ProfileZipInspection {
+        format: layout.format(),
+        root_prefix: layout.root_prefix.clone(),
+        file_count: 0,
+        total_uncompressed_size: 0,
+        metadata_name: None,
+        mods: Vec::new(),
+        unsafe_entries: Vec::new(),
+        symlink_entries: Vec::new(),
+        includes_bepinex: false,
+        includes_dotnet: false,
+        referenced_packages: Vec::new(),
+    };
+    let mut metadata_bytes: Option<Vec<u8>> = None;
+
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        let relative_path = match layout.classify(&entry) {
+            ArchiveEntryKind::Unsafe => {
+                inspection.unsafe_entries.push(entry.name().to_string());
+                continue;
+            }
+            ArchiveEntryKind::Symlink => {
+                inspection.symlink_entries.push(entry.name().to_string());
+                continue;
+            }
+            ArchiveEntryKind::Directory(_) | ArchiveEntryKind::Ignored => continue,
+            ArchiveEntryKind::File(relative_path) => relative_path,
+        };
+
+        inspection.file_count += 1;
+        inspection.total_uncompressed_size += entry.size();
+
+        let mut components = relative_path.components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(first)), Some(Component::Normal(second)))
+                if first.eq_ignore_ascii_case("bepinex") && second.eq_ignore_ascii_case("core") =>
+            {
+                inspection.includes_bepinex = true;
+            }
+            (Some(Component::Normal(first)), Some(_)) if first.eq_ignore_ascii_case("dotnet") => {
+                inspection.includes_dotnet = true;
+            }
+            _ => {}
+        }
+
+        if metadata_bytes.is_none()
+            && relative_path.components().count() == 1
+            && is_metadata_file(&relative_path)
+        {
+            let mut bytes = Vec::new();
+            entry.take(MAX_METADATA_BYTES).read_to_end(&mut bytes)?;
+            metadata_bytes = Some(bytes);
         }
     }
 
-    info!("Imported profile zip: {} -> {}", zip_path, destination);
-    Ok(ProfileImportResult {
-        metadata_name: metadata_name
-            .map(|n| n.trim().to_string())
-            .filter(|n| !n.is_empty()),
-    })
+    match layout.r2modman_export {
+        Some(export) => {
+            inspection.metadata_name = export.profile_name;
+            inspection.referenced_packages = export.packages;
+        }
+        None => {
+            if let Some(bytes) = metadata_bytes {
+                inspection.metadata_name = extract_name_from_metadata(&bytes)
+                    .map(|n| n.trim().to_string())
+                    .filter(|n| !n.is_empty());
+                inspection.mods = extract_mods_from_metadata(&bytes);
+            }
+        }
+    }
+
+    Ok(inspection)
 }
 
-fn add_directory_to_zip(
-    zip: &mut ZipWriter<File>,
+fn collect_export_entries(
     root_dir: &Path,
     current_dir: &Path,
     destination_path: &Path,
-    options: SimpleFileOptions,
-    sanitized_metadata: &str,
-    metadata_written: &mut bool,
+    entries: &mut Vec<ExportEntry>,
 ) -> AppResult<()> {
-    let entries = fs::read_dir(current_dir)?;
-    for entry in entries {
+    for entry in fs::read_dir(current_dir)? {
         let entry = entry?;
         let path = entry.path();
         if path == destination_path {
@@ -143,44 +654,43 @@ fn add_directory_to_zip(
         }
 
         if path.is_dir() {
-            zip.add_directory(format!("{zip_path}/"), options)?;
-            add_directory_to_zip(
-                zip,
-                root_dir,
-                &path,
-                destination_path,
-                options,
-                sanitized_metadata,
-                metadata_written,
-            )?;
+            entries.push(ExportEntry {
+                path: path.clone(),
+                zip_path,
+                is_dir: true,
+                size: 0,
+            });
+            collect_export_entries(root_dir, &path, destination_path, entries)?;
             continue;
         }
 
-        zip.start_file(zip_path, options)?;
-        if is_metadata_file(relative) {
-            *metadata_written = true;
-            zip.write_all(sanitized_metadata.as_bytes())?;
-        } else {
-            let mut file = File::open(&path)?;
-            std::io::copy(&mut file, zip)?;
-        }
+        entries.push(ExportEntry {
+            size: entry.metadata()?.len(),
+            path,
+            zip_path,
+            is_dir: false,
+        });
     }
 
     Ok(())
 }
 
-fn build_sanitized_metadata(profile_dir: &Path) -> AppResult<String> {
+pub fn build_sanitized_metadata(profile_dir: &Path) -> AppResult<String> {
     let metadata_path = profile_dir.join("metadata.json");
     let mut metadata = match fs::read_to_string(&metadata_path) {
         Ok(content) => parse_metadata_object(&content),
         Err(_) => Map::new(),
     };
+    profile_migrations::upgrade(&mut metadata)?;
 
     metadata.remove("id");
     metadata.remove("path");
     metadata.remove("created_at");
     metadata.remove("total_play_time");
-    metadata.insert("bepinex_installed".to_string(), Value::Bool(true));
+    // Describe what is actually being exported rather than what the source
+    // metadata claims.
+    let has_bepinex = profile_dir.join("BepInEx").join("core").is_dir();
+    metadata.insert("bepinex_installed".to_string(), Value::Bool(has_bepinex));
 
     if !metadata.contains_key("name") {
         metadata.insert(
@@ -272,14 +782,57 @@ fn extract_name_from_metadata(bytes: &[u8]) -> Option<String> {
         })
 }
 
-fn is_metadata_file(path: &Path) -> bool {
+fn extract_mods_from_metadata(bytes: &[u8]) -> Vec<ProfileZipMod> {
+    let Ok(value) = serde_json::from_slice::<Value>(bytes) else {
+        return Vec::new();
+    };
+
+    value
+        .get("mods")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| {
+            Some(ProfileZipMod {
+                mod_id: entry.get("mod_id")?.as_str()?.to_string(),
+                version: entry
+                    .get("version")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+pub fn is_metadata_file(path: &Path) -> bool {
     path.file_name()
         .and_then(|name| name.to_str())
         .map(|name| name.eq_ignore_ascii_case("metadata.json"))
         .unwrap_or(false)
 }
 
-fn should_skip_export_file(path: &Path) -> bool {
+/// Migration backups and staged writes of `metadata.json`. They hold the raw
+/// metadata, including the profile's absolute path, so they never leave the
+/// profile.
+pub fn is_metadata_leftover(path: &Path) -> bool {
+    if path.components().count() != 1 {
+        return false;
+    }
+    path.to_str()
+        .map(str::to_ascii_lowercase)
+        .is_some_and(|name| {
+            name == "metadata.json.tmp"
+                || (name.starts_with("metadata.json.") && name.ends_with(".bak"))
+        })
+}
+
+pub fn should_skip_export_file(path: &Path) -> bool {
+    // Play history is personal and starts over in imported or cloned profiles.
+    if path == Path::new(SESSION_LOG_FILE_NAME) || is_metadata_leftover(path) {
+        return true;
+    }
+
     let is_log_file = path
         .file_name()
         .and_then(|name| name.to_str())
@@ -299,7 +852,7 @@ fn should_skip_export_file(path: &Path) -> bool {
     })
 }
 
-fn to_zip_path(path: &Path) -> AppResult<String> {
+pub fn to_zip_path(path: &Path) -> AppResult<String> {
     let mut parts = Vec::new();
     for component in path.components() {
         match component {
@@ -319,6 +872,7 @@ fn to_zip_path(path: &Path) -> AppResult<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::services::profile_service;
 
     #[test]
     fn skip_bepinex_log_files() {
@@ -326,6 +880,42 @@ mod tests {
         assert!(!should_skip_export_file(Path::new("mods/LogOutput.log")));
     }
 
+    #[test]
+    fn export_leaves_out_migration_backups() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let profile_dir = root.join("old-1");
+        fs::create_dir_all(&profile_dir).unwrap();
+        let original = format!(
+            r#"{{"id":"old-1","name":"Old","path":"{}","created_at":1,"mods":[]}}"#,
+            profile_dir.display()
+        );
+        fs::write(profile_dir.join("metadata.json"), original).unwrap();
+        profile_service::read_profile(&profile_dir).unwrap();
+        assert!(profile_dir.join("metadata.json.v0.bak").exists());
+
+        let zip_path = root.join("old.zip");
+        let cancel = CancellationToken::default();
+        let mut on_progress = |_: &ArchiveProgress| {};
+        export_profile_zip(
+            profile_dir.to_string_lossy().to_string(),
+            zip_path.to_string_lossy().to_string(),
+            &mut ArchiveTracker::new(&cancel, &mut on_progress),
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        assert_eq!(names, vec!["metadata.json"]);
+        let mut metadata = String::new();
+        archive
+            .by_name("metadata.json")
+            .unwrap()
+            .read_to_string(&mut metadata)
+            .unwrap();
+        assert!(!metadata.contains(&*profile_dir.to_string_lossy()));
+    }
+
     #[test]
     fn zip_path_sanitization_rejects_parent_dir() {
         assert!(to_zip_path(Path::new("../evil")).is_err());
@@ -340,6 +930,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn metadata_mods_are_listed() {
+        let bytes = br#"{"mods":[{"mod_id":"a","version":"1.0.0"},{"version":"2"}]}"#;
+        let mods = extract_mods_from_metadata(bytes);
+        assert_eq!(mods.len(), 1);
+        assert_eq!(mods[0].mod_id, "a");
+    }
+
+    #[test]
+    fn failed_import_leaves_no_staging_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let zip_path = root.join("profile.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        zip.start_file("metadata.json", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"not json").unwrap();
+        zip.finish().unwrap();
+
+        let destination = root.join("profile");
+        let cancel = CancellationToken::default();
+        let mut on_progress = |_: &ArchiveProgress| {};
+        let result = import_profile_zip(
+            zip_path.to_string_lossy().to_string(),
+            destination.to_string_lossy().to_string(),
+            &ExtractionPolicy::default(),
+            ProfileImportMode::Create,
+            &mut ArchiveTracker::new(&cancel, &mut on_progress),
+        );
+        assert!(result.is_err());
+        assert!(!destination.exists());
+        assert!(!sibling_path(&destination, STAGING_SUFFIX).exists());
+    }
+
+    #[test]
+    fn inspection_classifies_r2modman_exports() {
+        let temp = tempfile::tempdir().unwrap();
+        let zip_path = temp.path().join("profile.r2z");
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(r2modman_service::EXPORT_FILE_NAME, options)
+            .unwrap();
+        zip.write_all(
+            b"profileName: Town of Us\nmods:\n  - name: Owner-Mod\n    version: {major: 1, minor: 2, patch: 3}\n    enabled: true\n",
+        )
+        .unwrap();
+        zip.start_file("config/mod.cfg", options).unwrap();
+        zip.write_all(b"a=1").unwrap();
+        zip.start_file("BepInEx/plugins/Mod.dll", options).unwrap();
+        zip.write_all(b"dll").unwrap();
+        zip.add_symlink("config/link.cfg", "/etc/passwd", options)
+            .unwrap();
+        zip.finish().unwrap();
+
+        let inspection = inspect_profile_zip(zip_path.to_string_lossy().to_string()).unwrap();
+        assert_eq!(inspection.format, ProfileArchiveFormat::R2modman);
+        assert_eq!(inspection.metadata_name.as_deref(), Some("Town of Us"));
+        assert_eq!(inspection.file_count, 1);
+        assert_eq!(inspection.symlink_entries, vec!["config/link.cfg"]);
+        assert_eq!(inspection.referenced_packages.len(), 1);
+        assert_eq!(
+            inspection.referenced_packages[0].version.as_deref(),
+            Some("1.2.3")
+        );
+    }
+
     #[test]
     fn strip_prefix_keeps_relative_path() {
         let stripped = strip_root_prefix(Path::new("profile/mods/file.dll"), Some("profile"));
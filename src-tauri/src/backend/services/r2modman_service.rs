@@ -0,0 +1,158 @@
+use crate::backend::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+pub const EXPORT_FILE_NAME: &str = "export.r2x";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct R2xExport {
+    profile_name: Option<String>,
+    #[serde(default)]
+    mods: Vec<R2xMod>,
+}
+
+#[derive(Deserialize)]
+struct R2xMod {
+    name: String,
+    version: Option<R2xVersion>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+struct R2xVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A Thunderstore package referenced by an r2modman export. Starlight does not
+/// install these itself; the frontend matches them against its own catalog.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReferencedPackage {
+    /// Full Thunderstore name, `Owner-PackageName`.
+    pub name: String,
+    pub owner: Option<String>,
+    pub package_name: String,
+    pub version: Option<String>,
+    pub enabled: bool,
+}
+
+pub struct R2modmanExport {
+    pub profile_name: Option<String>,
+    pub packages: Vec<ReferencedPackage>,
+}
+
+pub fn parse_export(content: &str) -> AppResult<R2modmanExport> {
+    let export: R2xExport = serde_yaml_ng::from_str(content)
+        .map_err(|e| AppError::validation(format!("Invalid {EXPORT_FILE_NAME}: {e}")))?;
+
+    let packages = export
+        .mods
+        .into_iter()
+        .map(|entry| {
+            let (owner, package_name) = match entry.name.split_once('-') {
+                Some((owner, package_name)) => (Some(owner.to_string()), package_name.to_string()),
+                None => (None, entry.name.clone()),
+            };
+            ReferencedPackage {
+                version: entry
+                    .version
+                    .map(|v| format!("{}.{}.{}", v.major, v.minor, v.patch)),
+                name: entry.name,
+                owner,
+                package_name,
+                enabled: entry.enabled,
+            }
+        })
+        .collect();
+
+    Ok(R2modmanExport {
+        profile_name: export
+            .profile_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty()),
+        packages,
+    })
+}
+
+/// Maps an r2modman archive entry onto the Starlight profile layout. Configs
+/// live under `config/` in r2modman exports and `BepInEx/config/` in
+/// Starlight profiles; everything else is ignored.
+pub fn map_entry_path(path: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    let first = match components.next()? {
+        Component::Normal(first) => first.to_string_lossy().to_lowercase(),
+        _ => return None,
+    };
+
+    let rest = match first.as_str() {
+        "config" => components.as_path(),
+        "bepinex" => match components.next()? {
+            Component::Normal(second) if second.eq_ignore_ascii_case("config") => {
+                components.as_path()
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    if rest.as_os_str().is_empty() {
+        return None;
+    }
+    Some(Path::new("BepInEx").join("config").join(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_r2x_packages() {
+        let export = parse_export(
+            r#"
+profileName: Town of Us
+mods:
+  - name: BepInEx-BepInExPack_AmongUs
+    version:
+      major: 6
+      minor: 0
+      patch: 688
+    enabled: true
+  - name: eDonnes124-Town_Of_Us_R
+    version:
+      major: 5
+      minor: 1
+      patch: 0
+    enabled: false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(export.profile_name.as_deref(), Some("Town of Us"));
+        assert_eq!(export.packages.len(), 2);
+        assert_eq!(export.packages[1].owner.as_deref(), Some("eDonnes124"));
+        assert_eq!(export.packages[1].package_name, "Town_Of_Us_R");
+        assert_eq!(export.packages[1].version.as_deref(), Some("5.1.0"));
+        assert!(!export.packages[1].enabled);
+    }
+
+    #[test]
+    fn config_entries_map_into_bepinex_config() {
+        assert_eq!(
+            map_entry_path(Path::new("config/mod.cfg")),
+            Some(PathBuf::from("BepInEx/config/mod.cfg"))
+        );
+        assert_eq!(
+            map_entry_path(Path::new("BepInEx/config/sub/mod.cfg")),
+            Some(PathBuf::from("BepInEx/config/sub/mod.cfg"))
+        );
+        assert_eq!(map_entry_path(Path::new("BepInEx/plugins/Mod.dll")), None);
+        assert_eq!(map_entry_path(Path::new("export.r2x")), None);
+    }
+}
@@ -0,0 +1,540 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::http_download::sibling_path;
+use crate::backend::services::lockfile_service::LOCKFILE_NAME;
+use crate::backend::services::mod_cache_service::{link_or_copy, sha256_file, walk_files};
+use crate::backend::services::profile_migrations;
+use crate::backend::services::profile_service::{self, METADATA_FILE_NAME, Profile};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
+
+const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+const OBJECTS_DIR_NAME: &str = "objects";
+const PROFILES_DIR_NAME: &str = "profiles";
+const RESTORE_SUFFIX: &str = "restore";
+/// Automatic snapshots covered by one taken inside this window are skipped, so
+/// repeated single-mod updates produce a single snapshot.
+const AUTO_SNAPSHOT_WINDOW_MS: u64 = 5 * 60 * 1000;
+const MAX_AUTO_SNAPSHOTS: usize = 10;
+const ROOT_FILES: [&str; 2] = [METADATA_FILE_NAME, LOCKFILE_NAME];
+const TRACKED_DIRS: [[&str; 2]; 2] = [["BepInEx", "config"], ["BepInEx", "plugins"]];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    Manual,
+    BeforeModUpdate,
+    /// Taken once per install batch or download queue run.
+    BeforeModInstall,
+    BeforeModRemoval,
+    BeforeBepinexReinstall,
+    BeforeRestore,
+    BeforeRepair,
+}
+
+impl SnapshotReason {
+    /// Whether a recent snapshot taken for `earlier` already captures the
+    /// state a snapshot for `self` would.
+    fn is_covered_by(self, earlier: SnapshotReason) -> bool {
+        self == earlier
+            || (self == SnapshotReason::BeforeModUpdate
+                && earlier == SnapshotReason::BeforeModInstall)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SnapshotFile {
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub created_at: u64,
+    pub reason: SnapshotReason,
+    pub label: Option<String>,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SnapshotManifest {
+    #[serde(flatten)]
+    info: SnapshotInfo,
+    /// Keyed by `/`-separated path relative to the profile root.
+    files: BTreeMap<String, SnapshotFile>,
+}
+
+pub fn snapshots_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(SNAPSHOTS_DIR_NAME))
+}
+
+fn profile_snapshots_dir(store: &Path, profile_dir: &Path) -> AppResult<PathBuf> {
+    let profile_id = profile_dir
+        .file_name()
+        .ok_or_else(|| AppError::validation("Profile path has no directory name"))?;
+    Ok(store.join(PROFILES_DIR_NAME).join(profile_id))
+}
+
+fn object_path(store: &Path, sha256: &str) -> PathBuf {
+    store.join(OBJECTS_DIR_NAME).join(&sha256[..2]).join(sha256)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn is_plugin_path(relative: &str) -> bool {
+    relative
+        .to_ascii_lowercase()
+        .starts_with("bepinex/plugins/")
+}
+
+/// Lists the snapshotted files of a profile as `/`-separated relative paths.
+fn tracked_files(profile_dir: &Path) -> AppResult<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    for name in ROOT_FILES {
+        let path = profile_dir.join(name);
+        if path.is_file() {
+            files.insert(name.to_string(), path);
+        }
+    }
+
+    for dir in TRACKED_DIRS {
+        let root = dir
+            .iter()
+            .fold(profile_dir.to_path_buf(), |path, part| path.join(part));
+        for file in walk_files(&root)? {
+            let relative = file
+                .strip_prefix(profile_dir)
+                .map_err(|e| AppError::other(e.to_string()))?;
+            let parts: Vec<String> = relative
+                .components()
+                .filter_map(|component| match component {
+                    Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                    _ => None,
+                })
+                .collect();
+            files.insert(parts.join("/"), file);
+        }
+    }
+    Ok(files)
+}
+
+fn read_manifest(path: &Path) -> AppResult<SnapshotManifest> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn manifest_path(store: &Path, profile_dir: &Path, snapshot_id: &str) -> AppResult<PathBuf> {
+    if snapshot_id.is_empty()
+        || !snapshot_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(AppError::validation(format!(
+            "Invalid snapshot id: {snapshot_id}"
+        )));
+    }
+    Ok(profile_snapshots_dir(store, profile_dir)?.join(format!("{snapshot_id}.json")))
+}
+
+pub fn create_snapshot(
+    store: &Path,
+    profile_dir: &Path,
+    reason: SnapshotReason,
+    label: Option<String>,
+) -> AppResult<SnapshotInfo> {
+    if !profile_dir.is_dir() {
+        return Err(AppError::validation(format!(
+            "Profile directory does not exist: {}",
+            profile_dir.display()
+        )));
+    }
+
+    let mut files = BTreeMap::new();
+    for (relative, path) in tracked_files(profile_dir)? {
+        let sha256 = sha256_file(&path)?;
+        let size = fs::metadata(&path)?.len();
+
+        let object = object_path(store, &sha256);
+        if !object.is_file() {
+            if let Some(parent) = object.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Plugins are only ever replaced by rename, so sharing their inode
+            // is safe; configs and metadata can be edited in place.
+            if is_plugin_path(&relative) {
+                link_or_copy(&path, &object)?;
+            } else {
+                let staging = sibling_path(&object, "tmp");
+                fs::copy(&path, &staging)?;
+                fs::rename(&staging, &object)?;
+            }
+        }
+        files.insert(relative, SnapshotFile { sha256, size });
+    }
+
+    let info = SnapshotInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: now_millis(),
+        reason,
+        label: label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty()),
+        file_count: files.len(),
+        total_bytes: files.values().map(|file| file.size).sum(),
+    };
+
+    let path = manifest_path(store, profile_dir, &info.id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let staging = sibling_path(&path, "tmp");
+    fs::write(
+        &staging,
+        serde_json::to_string_pretty(&SnapshotManifest {
+            info: info.clone(),
+            files,
+        })?,
+    )?;
+    fs::rename(&staging, &path)?;
+
+    info!(
+        "Created {:?} snapshot {} of {} ({} files)",
+        reason,
+        info.id,
+        profile_dir.display(),
+        info.file_count
+    );
+    Ok(info)
+}
+
+/// Newest first.
+pub fn list_snapshots(store: &Path, profile_dir: &Path) -> AppResult<Vec<SnapshotInfo>> {
+    let dir = profile_snapshots_dir(store, profile_dir)?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        match read_manifest(&path) {
+            Ok(manifest) => snapshots.push(manifest.info),
+            Err(e) => warn!("Skipping unreadable snapshot {:?}: {}", path, e),
+        }
+    }
+
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+    Ok(snapshots)
+}
+
+/// Takes an automatic snapshot unless a recent one already covers `reason`,
+/// then prunes old automatic snapshots.
+pub fn auto_snapshot(
+    store: &Path,
+    profile_dir: &Path,
+    reason: SnapshotReason,
+) -> AppResult<Option<SnapshotInfo>> {
+    let existing = list_snapshots(store, profile_dir)?;
+    let now = now_millis();
+    if existing.iter().any(|snapshot| {
+        reason.is_covered_by(snapshot.reason)
+            && now.saturating_sub(snapshot.created_at) < AUTO_SNAPSHOT_WINDOW_MS
+    }) {
+        return Ok(None);
+    }
+
+    create_and_prune(store, profile_dir, reason, &existing).map(Some)
+}
+
+/// Takes an automatic snapshot for a batch of changes regardless of recent
+/// snapshots; the caller guarantees it runs once per batch.
+pub fn batch_snapshot(
+    store: &Path,
+    profile_dir: &Path,
+    reason: SnapshotReason,
+) -> AppResult<SnapshotInfo> {
+    let existing = list_snapshots(store, profile_dir)?;
+    create_and_prune(store, profile_dir, reason, &existing)
+}
+
+fn create_and_prune(
+    store: &Path,
+    profile_dir: &Path,
+    reason: SnapshotReason,
+    existing: &[SnapshotInfo],
+) -> AppResult<SnapshotInfo> {
+    let created = create_snapshot(store, profile_dir, reason, None)?;
+
+    let stale: Vec<&SnapshotInfo> = existing
+        .iter()
+        .filter(|snapshot| snapshot.reason != SnapshotReason::Manual)
+        .skip(MAX_AUTO_SNAPSHOTS - 1)
+        .collect();
+    if !stale.is_empty() {
+        for snapshot in stale {
+            fs::remove_file(manifest_path(store, profile_dir, &snapshot.id)?).ok();
+        }
+        collect_garbage(store)?;
+    }
+
+    Ok(created)
+}
+
+/// Best-effort automatic snapshot used before destructive operations; a
+/// failure is logged and never blocks the operation itself.
+pub fn snapshot_before<R: Runtime>(app: &AppHandle<R>, profile_dir: &Path, reason: SnapshotReason) {
+    let result = snapshots_dir(app).and_then(|store| auto_snapshot(&store, profile_dir, reason));
+    if let Err(e) = result {
+        warn!(
+            "Failed to snapshot {} before {:?}: {}",
+            profile_dir.display(),
+            reason,
+            e
+        );
+    }
+}
+
+/// Best-effort [`batch_snapshot`], taken once before a batch of mod changes.
+pub fn snapshot_before_batch<R: Runtime>(
+    app: &AppHandle<R>,
+    profile_dir: &Path,
+    reason: SnapshotReason,
+) {
+    let result = snapshots_dir(app).and_then(|store| batch_snapshot(&store, profile_dir, reason));
+    if let Err(e) = result {
+        warn!(
+            "Failed to snapshot {} before {:?}: {}",
+            profile_dir.display(),
+            reason,
+            e
+        );
+    }
+}
+
+/// Brings the snapshotted areas of the profile back to the snapshot state.
+/// Files added since the snapshot are removed; unchanged files are untouched.
+pub fn restore_snapshot(
+    store: &Path,
+    profile_dir: &Path,
+    snapshot_id: &str,
+) -> AppResult<SnapshotInfo> {
+    let manifest = read_manifest(&manifest_path(store, profile_dir, snapshot_id)?)?;
+    for (relative, file) in &manifest.files {
+        if !object_path(store, &file.sha256).is_file() {
+            return Err(AppError::validation(format!(
+                "Snapshot {snapshot_id} is incomplete: {relative} is missing from the store"
+            )));
+        }
+    }
+
+    if let Err(e) = auto_snapshot(store, profile_dir, SnapshotReason::BeforeRestore) {
+        warn!("Failed to snapshot profile before restore: {}", e);
+    }
+
+    let current = tracked_files(profile_dir)?;
+    for (relative, path) in &current {
+        if !manifest.files.contains_key(relative) {
+            fs::remove_file(path)?;
+        }
+    }
+
+    for (relative, file) in &manifest.files {
+        let target = profile_dir.join(relative);
+        if let Some(existing) = current.get(relative)
+            && sha256_file(existing)? == file.sha256
+        {
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let object = object_path(store, &file.sha256);
+        if relative == METADATA_FILE_NAME && profile_service::read_profile(profile_dir).is_ok() {
+            restore_metadata(profile_dir, &object)?;
+        } else if is_plugin_path(relative) {
+            link_or_copy(&object, &target)?;
+        } else {
+            let staging = sibling_path(&target, RESTORE_SUFFIX);
+            fs::copy(&object, &staging)?;
+            fs::rename(&staging, &target)?;
+        }
+    }
+
+    info!(
+        "Restored snapshot {} into {}",
+        snapshot_id,
+        profile_dir.display()
+    );
+    Ok(manifest.info)
+}
+
+/// Play time is recorded by the game runtime and is not part of a profile's
+/// state, so `last_launched_at` and `total_play_time` keep their current values.
+fn restore_metadata(profile_dir: &Path, object: &Path) -> AppResult<()> {
+    let Value::Object(mut metadata) = serde_json::from_str(&fs::read_to_string(object)?)? else {
+        return Err(AppError::validation(
+            "Snapshot metadata must contain a JSON object",
+        ));
+    };
+    profile_migrations::upgrade(&mut metadata)?;
+    let restored: Profile = serde_json::from_value(Value::Object(metadata))?;
+
+    profile_service::modify_profile_at(profile_dir, |profile| {
+        *profile = Profile {
+            path: profile.path.clone(),
+            last_launched_at: profile.last_launched_at,
+            total_play_time: profile.total_play_time,
+            ..restored
+        };
+        Ok(())
+    })?;
+    Ok(())
+}
+
+pub fn delete_snapshot(store: &Path, profile_dir: &Path, snapshot_id: &str) -> AppResult<()> {
+    fs::remove_file(manifest_path(store, profile_dir, snapshot_id)?)?;
+    collect_garbage(store)?;
+    Ok(())
+}
+
+/// Removes stored objects no snapshot of any profile refers to.
+fn collect_garbage(store: &Path) -> AppResult<()> {
+    let mut referenced = HashSet::new();
+    for manifest in walk_files(&store.join(PROFILES_DIR_NAME))? {
+        if manifest.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(manifest) = read_manifest(&manifest) else {
+            // Keep everything rather than risk deleting objects we cannot see.
+            return Ok(());
+        };
+        referenced.extend(manifest.files.into_values().map(|file| file.sha256));
+    }
+
+    let mut removed = 0;
+    for object in walk_files(&store.join(OBJECTS_DIR_NAME))? {
+        let name = object
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !referenced.contains(&name) {
+            fs::remove_file(&object)?;
+            removed += 1;
+        }
+    }
+
+    if removed > 0 {
+        info!("Removed {} unreferenced snapshot objects", removed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::services::play_time_service::{self, PlaySession};
+
+    #[test]
+    fn snapshot_restores_configs_and_plugins() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let store = root.join("store");
+        let profile = root.join("profile-1");
+        let plugins = profile.join("BepInEx").join("plugins");
+        let config = profile.join("BepInEx").join("config");
+        fs::create_dir_all(&plugins).unwrap();
+        fs::create_dir_all(&config).unwrap();
+        fs::write(profile.join("metadata.json"), "{}").unwrap();
+        fs::write(plugins.join("Mod.dll"), "v1").unwrap();
+        fs::write(config.join("mod.cfg"), "a=1").unwrap();
+
+        let snapshot = create_snapshot(&store, &profile, SnapshotReason::Manual, None).unwrap();
+        assert_eq!(snapshot.file_count, 3);
+
+        fs::write(plugins.join("Mod.dll.new"), "v2").unwrap();
+        fs::rename(plugins.join("Mod.dll.new"), plugins.join("Mod.dll")).unwrap();
+        fs::write(plugins.join("Extra.dll"), "extra").unwrap();
+        fs::write(config.join("mod.cfg"), "a=2").unwrap();
+
+        restore_snapshot(&store, &profile, &snapshot.id).unwrap();
+        assert_eq!(fs::read_to_string(plugins.join("Mod.dll")).unwrap(), "v1");
+        assert_eq!(fs::read_to_string(config.join("mod.cfg")).unwrap(), "a=1");
+        assert!(!plugins.join("Extra.dll").exists());
+
+        let snapshots = list_snapshots(&store, &profile).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        for snapshot in snapshots {
+            delete_snapshot(&store, &profile, &snapshot.id).unwrap();
+        }
+        assert!(
+            walk_files(&store.join(OBJECTS_DIR_NAME))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn restore_keeps_play_time_recorded_since_the_snapshot() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let store = root.join("store");
+        let profiles_dir = root.join("profiles");
+        let created = profile_service::create_profile(&profiles_dir, "Before").unwrap();
+        let profile_dir = PathBuf::from(&created.path);
+        let snapshot = create_snapshot(&store, &profile_dir, SnapshotReason::Manual, None).unwrap();
+
+        profile_service::rename_profile(&profiles_dir, &created.id, "After").unwrap();
+        play_time_service::record_launch(&profiles_dir, &created.id, 1_000).unwrap();
+        let session = PlaySession {
+            started_at: 1_000,
+            ended_at: 6_000,
+            duration_ms: 5_000,
+            counted_ms: 5_000,
+            platform: "steam".to_string(),
+        };
+        play_time_service::record_session(&profiles_dir, &created.id, &session).unwrap();
+
+        restore_snapshot(&store, &profile_dir, &snapshot.id).unwrap();
+        let restored = profile_service::get_profile(&profiles_dir, &created.id).unwrap();
+        assert_eq!(restored.name, "Before");
+        assert_eq!(restored.last_launched_at, Some(1_000));
+        assert_eq!(restored.total_play_time, Some(5_000));
+    }
+
+    #[test]
+    fn auto_snapshots_are_debounced() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let store = root.join("store");
+        let profile = root.join("profile-1");
+        fs::create_dir_all(&profile).unwrap();
+        fs::write(profile.join("metadata.json"), "{}").unwrap();
+
+        let reason = SnapshotReason::BeforeModUpdate;
+        assert!(auto_snapshot(&store, &profile, reason).unwrap().is_some());
+        assert!(auto_snapshot(&store, &profile, reason).unwrap().is_none());
+
+        // Batches always snapshot, and cover the per-mod update snapshots
+        // taken during them.
+        batch_snapshot(&store, &profile, SnapshotReason::BeforeModInstall).unwrap();
+        batch_snapshot(&store, &profile, SnapshotReason::BeforeModInstall).unwrap();
+        assert_eq!(list_snapshots(&store, &profile).unwrap().len(), 3);
+        assert!(SnapshotReason::BeforeModUpdate.is_covered_by(SnapshotReason::BeforeModInstall));
+        assert!(!SnapshotReason::BeforeModRemoval.is_covered_by(SnapshotReason::BeforeModInstall));
+    }
+}
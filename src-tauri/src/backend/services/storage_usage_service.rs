@@ -0,0 +1,313 @@
+use crate::backend::error::AppResult;
+use crate::backend::services::mod_cache_service::walk_files;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// `app_data/cache` doubles as the Epic login webview's data directory and
+/// the home of the cached BepInEx archive.
+const WEBVIEW_CACHE_DIR_NAME: &str = "cache";
+const BEPINEX_CACHE_FILE_NAME: &str = "bepinex.zip";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Category {
+    BepinexCore,
+    Dotnet,
+    Plugins,
+    Configs,
+    Logs,
+    ProfileOther,
+    BepinexCache,
+    WebviewCache,
+    ModCache,
+    Snapshots,
+    Other,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProfileStorageUsage {
+    pub profile_id: String,
+    pub bepinex_core_bytes: u64,
+    pub dotnet_bytes: u64,
+    pub plugins_bytes: u64,
+    pub configs_bytes: u64,
+    pub logs_bytes: u64,
+    pub other_bytes: u64,
+    pub total_bytes: u64,
+    /// Part of `total_bytes` hard-linked with files elsewhere in the app data
+    /// directory, such as other profiles or the mod cache.
+    pub shared_bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct StorageUsageReport {
+    pub profiles: Vec<ProfileStorageUsage>,
+    /// The shared areas below exclude files hard-linked into a profile.
+    pub bepinex_cache_bytes: u64,
+    pub webview_cache_bytes: u64,
+    pub mod_cache_bytes: u64,
+    pub snapshots_bytes: u64,
+    pub other_bytes: u64,
+    /// Actual disk usage: every hard-linked file is counted once.
+    pub total_bytes: u64,
+}
+
+pub struct StorageLocations {
+    pub app_data_dir: PathBuf,
+    pub profiles_dir: PathBuf,
+    pub mod_cache_dir: PathBuf,
+    pub snapshots_dir: PathBuf,
+}
+
+struct FileUsage {
+    owner: Option<String>,
+    category: Category,
+    size: u64,
+    identity: Option<(u64, u64)>,
+}
+
+#[cfg(unix)]
+fn file_identity(_path: &Path, metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+fn file_identity(path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::{
+        BY_HANDLE_FILE_INFORMATION, GetFileInformationByHandle,
+    };
+
+    let file = fs::File::open(path).ok()?;
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &mut info) }.ok()?;
+    Some((
+        u64::from(info.dwVolumeSerialNumber),
+        (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow),
+    ))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_identity(_path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+fn normal_parts(relative: &Path) -> Vec<String> {
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn profile_category(relative: &Path) -> Category {
+    let parts = normal_parts(relative);
+    let is_log = parts.last().is_some_and(|name| name.ends_with(".log"));
+
+    match parts.first().map(String::as_str) {
+        Some("dotnet") => Category::Dotnet,
+        Some("bepinex") => match parts.get(1).map(String::as_str) {
+            _ if is_log => Category::Logs,
+            Some("plugins") => Category::Plugins,
+            Some("config") => Category::Configs,
+            _ => Category::BepinexCore,
+        },
+        _ => Category::ProfileOther,
+    }
+}
+
+fn classify(locations: &StorageLocations, path: &Path) -> (Option<String>, Category) {
+    if let Ok(relative) = path.strip_prefix(&locations.profiles_dir) {
+        let mut components = relative.components();
+        if let Some(Component::Normal(profile_id)) = components.next() {
+            let category = profile_category(components.as_path());
+            return (Some(profile_id.to_string_lossy().to_string()), category);
+        }
+        return (None, Category::Other);
+    }
+    if path.starts_with(&locations.mod_cache_dir) {
+        return (None, Category::ModCache);
+    }
+    if path.starts_with(&locations.snapshots_dir) {
+        return (None, Category::Snapshots);
+    }
+
+    let webview_cache = locations.app_data_dir.join(WEBVIEW_CACHE_DIR_NAME);
+    if path == webview_cache.join(BEPINEX_CACHE_FILE_NAME) {
+        return (None, Category::BepinexCache);
+    }
+    if path.starts_with(&webview_cache) {
+        return (None, Category::WebviewCache);
+    }
+    (None, Category::Other)
+}
+
+pub fn usage_report(locations: &StorageLocations) -> AppResult<StorageUsageReport> {
+    let mut files = Vec::new();
+    let mut owners_by_identity: HashMap<(u64, u64), Vec<Option<String>>> = HashMap::new();
+
+    for path in walk_files(&locations.app_data_dir)? {
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        let (owner, category) = classify(locations, &path);
+        let identity = file_identity(&path, &metadata);
+        if let Some(identity) = identity {
+            owners_by_identity
+                .entry(identity)
+                .or_default()
+                .push(owner.clone());
+        }
+        files.push(FileUsage {
+            owner,
+            category,
+            size: metadata.len(),
+            identity,
+        });
+    }
+
+    let mut report = StorageUsageReport::default();
+    let mut profiles: HashMap<String, ProfileStorageUsage> = HashMap::new();
+    let mut counted_globally = HashSet::new();
+    let mut counted_per_owner = HashSet::new();
+
+    for file in files {
+        let first_globally = file
+            .identity
+            .is_none_or(|identity| counted_globally.insert(identity));
+        if first_globally {
+            report.total_bytes += file.size;
+        }
+
+        let Some(owner) = file.owner else {
+            // Bytes already accounted to a profile through a hard link are not
+            // counted again for the cache or snapshot store.
+            let linked_to_profile = file.identity.is_some_and(|identity| {
+                owners_by_identity
+                    .get(&identity)
+                    .is_some_and(|owners| owners.iter().any(Option::is_some))
+            });
+            if first_globally && !linked_to_profile {
+                let bucket = match file.category {
+                    Category::BepinexCache => &mut report.bepinex_cache_bytes,
+                    Category::WebviewCache => &mut report.webview_cache_bytes,
+                    Category::ModCache => &mut report.mod_cache_bytes,
+                    Category::Snapshots => &mut report.snapshots_bytes,
+                    _ => &mut report.other_bytes,
+                };
+                *bucket += file.size;
+            }
+            continue;
+        };
+
+        let first_in_profile = file
+            .identity
+            .is_none_or(|identity| counted_per_owner.insert((owner.clone(), identity)));
+        if !first_in_profile {
+            continue;
+        }
+
+        let shared = file.identity.is_some_and(|identity| {
+            owners_by_identity
+                .get(&identity)
+                .is_some_and(|owners| owners.iter().any(|other| other.as_ref() != Some(&owner)))
+        });
+        let usage = profiles
+            .entry(owner.clone())
+            .or_insert_with(|| ProfileStorageUsage {
+                profile_id: owner,
+                ..ProfileStorageUsage::default()
+            });
+        let bucket = match file.category {
+            Category::BepinexCore => &mut usage.bepinex_core_bytes,
+            Category::Dotnet => &mut usage.dotnet_bytes,
+            Category::Plugins => &mut usage.plugins_bytes,
+            Category::Configs => &mut usage.configs_bytes,
+            Category::Logs => &mut usage.logs_bytes,
+            _ => &mut usage.other_bytes,
+        };
+        *bucket += file.size;
+        usage.total_bytes += file.size;
+        if shared {
+            usage.shared_bytes += file.size;
+        }
+    }
+
+    report.profiles = profiles.into_values().collect();
+    report
+        .profiles
+        .sort_by_key(|profile| std::cmp::Reverse(profile.total_bytes));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_are_split_by_category() {
+        assert_eq!(
+            profile_category(Path::new("BepInEx/core/BepInEx.Core.dll")),
+            Category::BepinexCore
+        );
+        assert_eq!(
+            profile_category(Path::new("BepInEx/LogOutput.log")),
+            Category::Logs
+        );
+        assert_eq!(
+            profile_category(Path::new("BepInEx/plugins/Mod.dll")),
+            Category::Plugins
+        );
+        assert_eq!(
+            profile_category(Path::new("dotnet/coreclr.dll")),
+            Category::Dotnet
+        );
+        assert_eq!(
+            profile_category(Path::new("metadata.json")),
+            Category::ProfileOther
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let locations = StorageLocations {
+            app_data_dir: root.to_path_buf(),
+            profiles_dir: root.join("profiles"),
+            mod_cache_dir: root.join("mod-cache"),
+            snapshots_dir: root.join("snapshots"),
+        };
+        let plugins_a = locations
+            .profiles_dir
+            .join("a")
+            .join("BepInEx")
+            .join("plugins");
+        let plugins_b = locations
+            .profiles_dir
+            .join("b")
+            .join("BepInEx")
+            .join("plugins");
+        fs::create_dir_all(&plugins_a).unwrap();
+        fs::create_dir_all(&plugins_b).unwrap();
+        fs::create_dir_all(root.join("cache")).unwrap();
+        fs::write(plugins_a.join("Mod.dll"), vec![0u8; 100]).unwrap();
+        fs::hard_link(plugins_a.join("Mod.dll"), plugins_b.join("Mod.dll")).unwrap();
+        fs::write(root.join("cache").join("bepinex.zip"), vec![0u8; 10]).unwrap();
+
+        let report = usage_report(&locations).unwrap();
+        assert_eq!(report.total_bytes, 110);
+        assert_eq!(report.bepinex_cache_bytes, 10);
+        assert_eq!(report.profiles.len(), 2);
+        for profile in &report.profiles {
+            assert_eq!(profile.plugins_bytes, 100);
+            assert_eq!(profile.shared_bytes, 100);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::backend::error::{AppError, AppResult};
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager, Runtime};
+
+const COMPAT_DIR_NAME: &str = "compat";
+const VANILLA_PREFIX_NAME: &str = "vanilla";
+/// Doorstop is a `winhttp.dll` proxy that Wine only finds in the game
+/// directory, where modded launches stage it from the profile. Wine picks it
+/// up over its builtin only when told to prefer native, and vanilla launches
+/// pin the builtin so a staged proxy is ignored.
+const DOORSTOP_DLL_OVERRIDES: &str = "winhttp=n,b";
+const BUILTIN_DLL_OVERRIDES: &str = "winhttp=b";
+
+/// How Windows builds of the game are run on Linux.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum CompatRuntime {
+    /// A Wine binary; `wine` from `PATH` when not set.
+    #[serde(rename_all = "camelCase")]
+    Wine { binary: Option<String> },
+    /// A Proton install directory, the one containing the `proton` script.
+    #[serde(rename_all = "camelCase")]
+    Proton { install_dir: String },
+}
+
+impl Default for CompatRuntime {
+    fn default() -> Self {
+        Self::Wine { binary: None }
+    }
+}
+
+/// Root of the per-profile prefixes. These live outside the profile
+/// directories so exports, snapshots and clones never pick them up.
+pub fn compat_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(COMPAT_DIR_NAME))
+}
+
+/// Prefix directory for a profile, or the shared vanilla prefix.
+pub fn prefix_dir(compat_dir: &Path, profile_id: Option<&str>) -> AppResult<PathBuf> {
+    let name = profile_id.unwrap_or(VANILLA_PREFIX_NAME);
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(AppError::validation(format!("Invalid profile id: {name}")));
+    }
+    Ok(compat_dir.join(name))
+}
+
+/// Wine maps `Z:` to the Unix root, so absolute Unix paths are reachable from
+/// inside any prefix under that drive.
+pub fn to_wine_path(path: &str) -> String {
+    if path.starts_with('/') {
+        format!("Z:{}", path.replace('/', "\\"))
+    } else {
+        path.to_string()
+    }
+}
+
+fn steam_root() -> Option<PathBuf> {
+    let home = home::home_dir()?;
+    [".steam/steam", ".local/share/Steam"]
+        .iter()
+        .map(|sub_path| home.join(sub_path))
+        .find(|path| path.is_dir())
+}
+
+/// Builds a command that runs `game_exe` through the configured runtime with
+/// its own prefix. Game arguments are appended by the caller. `load_proxy`
+/// decides whether a doorstop proxy in the game directory is loaded.
+pub fn build_command(
+    runtime: &CompatRuntime,
+    prefix_dir: &Path,
+    game_exe: &str,
+    load_proxy: bool,
+) -> AppResult<Command> {
+    let mut cmd = match runtime {
+        CompatRuntime::Wine { binary } => {
+            let wine_prefix = prefix_dir.join("pfx");
+            fs::create_dir_all(&wine_prefix)?;
+            let mut cmd = Command::new(binary.as_deref().unwrap_or("wine"));
+            cmd.env("WINEPREFIX", &wine_prefix).arg(game_exe);
+            info!(
+                "Launching through Wine with prefix {}",
+                wine_prefix.display()
+            );
+            cmd
+        }
+        CompatRuntime::Proton { install_dir } => {
+            let proton = Path::new(install_dir).join("proton");
+            if !proton.is_file() {
+                return Err(AppError::validation(format!(
+                    "No proton script found in {install_dir}"
+                )));
+            }
+            // Proton creates `pfx` inside the compat data directory itself.
+            fs::create_dir_all(prefix_dir)?;
+            let mut cmd = Command::new(&proton);
+            cmd.env("STEAM_COMPAT_DATA_PATH", prefix_dir)
+                .env(
+                    "STEAM_COMPAT_CLIENT_INSTALL_PATH",
+                    steam_root().unwrap_or_else(|| prefix_dir.to_path_buf()),
+                )
+                // `waitforexitandrun` keeps the child alive for as long as the
+                // game runs, so game_runtime tracks the real session.
+                .arg("waitforexitandrun")
+                .arg(game_exe);
+            info!(
+                "Launching through Proton {} with compat data {}",
+                install_dir,
+                prefix_dir.display()
+            );
+            cmd
+        }
+    };
+
+    let overrides = if load_proxy {
+        DOORSTOP_DLL_OVERRIDES
+    } else {
+        BUILTIN_DLL_OVERRIDES
+    };
+    cmd.env("WINEDLLOVERRIDES", overrides);
+    if let Some(game_dir) = Path::new(game_exe).parent() {
+        cmd.current_dir(game_dir);
+    }
+    Ok(cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_paths_map_to_z_drive() {
+        assert_eq!(
+            to_wine_path("/home/user/profiles/p1/BepInEx/core/BepInEx.dll"),
+            "Z:\\home\\user\\profiles\\p1\\BepInEx\\core\\BepInEx.dll"
+        );
+        assert_eq!(to_wine_path("C:\\Games"), "C:\\Games");
+    }
+
+    #[test]
+    fn wine_command_uses_profile_prefix() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let prefix = prefix_dir(root, Some("p1")).unwrap();
+        let cmd = build_command(
+            &CompatRuntime::default(),
+            &prefix,
+            "/games/Among Us/Among Us.exe",
+            true,
+        )
+        .unwrap();
+
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(
+            std::ffi::OsStr::new("WINEPREFIX"),
+            Some(prefix.join("pfx").as_os_str())
+        )));
+        assert!(envs.contains(&(
+            std::ffi::OsStr::new("WINEDLLOVERRIDES"),
+            Some(std::ffi::OsStr::new("winhttp=n,b"))
+        )));
+        assert!(prefix_dir(root, Some("../escape")).is_err());
+    }
+}
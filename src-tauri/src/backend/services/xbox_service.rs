@@ -1,7 +1,8 @@
 use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::doorstop_service;
 use crate::backend::state::game_runtime;
 use log::{debug, info};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tauri::{AppHandle, Runtime};
 
@@ -59,75 +60,19 @@ pub fn get_xbox_app_id() -> AppResult<String> {
 }
 
 pub fn prepare_xbox_launch(args: XboxPrepareLaunchArgs) -> AppResult<()> {
-    let game_dir = PathBuf::from(&args.game_dir);
     let profile_path = PathBuf::from(&args.profile_path);
-
-    let src_dll = profile_path.join("winhttp.dll");
-    let src_ini = profile_path.join("doorstop_config.ini");
-
-    let dst_dll = game_dir.join("winhttp.dll");
-    let dst_ini = game_dir.join("doorstop_config.ini");
-
-    if !src_dll.exists() {
-        return Err(AppError::validation(
-            "winhttp.dll not found in profile. Please wait for BepInEx installation to complete.",
-        ));
-    }
-    if !src_ini.exists() {
-        return Err(AppError::validation(
-            "doorstop_config.ini not found in profile. Please wait for BepInEx installation to complete.",
-        ));
-    }
-
-    std::fs::copy(&src_dll, &dst_dll)?;
-
-    let cleanup_dll = || {
-        if dst_dll.exists() {
-            let _ = std::fs::remove_file(&dst_dll);
-        }
-    };
-
-    let ini_content = std::fs::read_to_string(&src_ini).map_err(|e| {
-        cleanup_dll();
-        AppError::process(format!("Failed to read doorstop_config.ini: {e}"))
-    })?;
-
     let target_assembly = profile_path
         .join("BepInEx")
         .join("core")
         .join("BepInEx.Unity.IL2CPP.dll");
     let coreclr_path = profile_path.join("dotnet").join("coreclr.dll");
 
-    let target_assembly_str = target_assembly.to_string_lossy().replace('\\', "\\\\");
-    let coreclr_path_str = coreclr_path.to_string_lossy().replace('\\', "\\\\");
-
-    let mut modified_content = String::new();
-    for line in ini_content.lines() {
-        let trimmed = line.trim();
-        if !trimmed.starts_with('#')
-            && !trimmed.starts_with(';')
-            && trimmed.starts_with("target_assembly")
-            && trimmed.contains('=')
-        {
-            modified_content.push_str(&format!("target_assembly = \"{}\"\n", target_assembly_str));
-        } else if !trimmed.starts_with('#')
-            && !trimmed.starts_with(';')
-            && trimmed.starts_with("coreclr_path")
-            && trimmed.contains('=')
-        {
-            modified_content.push_str(&format!("coreclr_path = \"{}\"\n", coreclr_path_str));
-        } else {
-            modified_content.push_str(line);
-            modified_content.push('\n');
-        }
-    }
-
-    std::fs::write(&dst_ini, modified_content).map_err(|e| {
-        cleanup_dll();
-        AppError::process(format!("Failed to write doorstop_config.ini: {e}"))
-    })?;
-
-    Ok(())
+    doorstop_service::stage_proxy(
+        Path::new(&args.game_dir),
+        &profile_path,
+        &target_assembly.to_string_lossy(),
+        &coreclr_path.to_string_lossy(),
+    )
 }
 
 pub fn launch_xbox<R: Runtime>(app: AppHandle<R>, args: XboxLaunchArgs) -> AppResult<()> {
@@ -145,15 +90,5 @@ pub fn launch_xbox<R: Runtime>(app: AppHandle<R>, args: XboxLaunchArgs) -> AppRe
 }
 
 pub fn cleanup_xbox_files(args: XboxCleanupArgs) -> AppResult<()> {
-    let game_dir = PathBuf::from(&args.game_dir);
-    let dll_path = game_dir.join("winhttp.dll");
-    let ini_path = game_dir.join("doorstop_config.ini");
-
-    if dll_path.exists() {
-        std::fs::remove_file(&dll_path)?;
-    }
-    if ini_path.exists() {
-        std::fs::remove_file(&ini_path)?;
-    }
-    Ok(())
+    doorstop_service::remove_proxy(Path::new(&args.game_dir))
 }
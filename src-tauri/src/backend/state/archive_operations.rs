@@ -0,0 +1,118 @@
+use crate::backend::error::{AppError, AppResult};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tauri::{AppHandle, Emitter, Runtime};
+
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn check(&self) -> AppResult<()> {
+        if self.is_cancelled() {
+            return Err(AppError::other("Operation cancelled"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default, serde::Serialize)]
+pub struct ArchiveProgress {
+    pub stage: String,
+    pub bytes_processed: u64,
+    pub total_bytes: u64,
+    pub entries_processed: usize,
+    pub total_entries: usize,
+    pub current_file: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct ProfileArchiveProgressPayload {
+    pub operation_id: String,
+    pub operation: String,
+    #[serde(flatten)]
+    pub progress: ArchiveProgress,
+}
+
+static ARCHIVE_OPERATIONS: LazyLock<Mutex<HashMap<String, CancellationToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A registered export/import. The cancellation token is unregistered when the
+/// handle is dropped.
+pub struct ArchiveOperation<R: Runtime> {
+    app: AppHandle<R>,
+    pub operation_id: String,
+    operation: &'static str,
+    pub token: CancellationToken,
+}
+
+impl<R: Runtime> ArchiveOperation<R> {
+    pub fn emit(&self, progress: &ArchiveProgress) {
+        let _ = self.app.emit(
+            "profile-archive-progress",
+            ProfileArchiveProgressPayload {
+                operation_id: self.operation_id.clone(),
+                operation: self.operation.to_string(),
+                progress: progress.clone(),
+            },
+        );
+    }
+}
+
+impl<R: Runtime> Drop for ArchiveOperation<R> {
+    fn drop(&mut self) {
+        match ARCHIVE_OPERATIONS.lock() {
+            Ok(mut operations) => {
+                operations.remove(&self.operation_id);
+            }
+            Err(_) => warn!("Failed to acquire archive operations lock"),
+        }
+    }
+}
+
+pub fn register<R: Runtime>(
+    app: &AppHandle<R>,
+    operation: &'static str,
+    operation_id: Option<String>,
+) -> AppResult<ArchiveOperation<R>> {
+    let operation_id = operation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let token = CancellationToken::default();
+
+    let mut operations = ARCHIVE_OPERATIONS
+        .lock()
+        .map_err(|_| AppError::state("Failed to acquire archive operations lock"))?;
+    if operations.contains_key(&operation_id) {
+        return Err(AppError::validation(format!(
+            "Archive operation {operation_id} is already running"
+        )));
+    }
+    operations.insert(operation_id.clone(), token.clone());
+
+    Ok(ArchiveOperation {
+        app: app.clone(),
+        operation_id,
+        operation,
+        token,
+    })
+}
+
+pub fn cancel(operation_id: &str) -> AppResult<bool> {
+    let operations = ARCHIVE_OPERATIONS
+        .lock()
+        .map_err(|_| AppError::state("Failed to acquire archive operations lock"))?;
+    let Some(token) = operations.get(operation_id) else {
+        return Ok(false);
+    };
+    info!("Cancelling archive operation {}", operation_id);
+    token.cancel();
+    Ok(true)
+}
@@ -0,0 +1,547 @@
+use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::bepinex_service::{self, BepInExInstallRequest};
+use crate::backend::services::http_retry::RetryPolicy;
+use crate::backend::services::mod_download_service::{self, ModDownloadRequest};
+use crate::backend::services::snapshot_service::{self, SnapshotReason};
+use crate::backend::state::archive_operations::CancellationToken;
+use log::{error, info, warn};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Emitter, Runtime};
+use uuid::Uuid;
+
+const DEFAULT_MAX_PARALLEL: usize = 3;
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DownloadRequest {
+    #[serde(rename_all = "camelCase")]
+    Mod {
+        #[serde(flatten)]
+        download: ModDownloadRequest,
+        max_attempts: Option<u32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Bepinex {
+        #[serde(flatten)]
+        install: BepInExInstallRequest,
+        max_attempts: Option<u32>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadJobStatus {
+    Queued,
+    Running,
+    Cancelling,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct DownloadJobInfo {
+    pub job_id: String,
+    pub kind: String,
+    pub mod_id: Option<String>,
+    pub status: DownloadJobStatus,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct DownloadQueuePayload {
+    pub max_parallel: usize,
+    pub jobs: Vec<DownloadJobInfo>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct DownloadJobFinishedPayload {
+    pub job_id: String,
+    pub mod_id: Option<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+struct QueuedJob {
+    info: DownloadJobInfo,
+    request: DownloadRequest,
+}
+
+/// Running jobs are cancelled through their token rather than aborted, so a
+/// BepInEx install is never cut off half-way through an extraction. The job
+/// stops at its next check and cleans up after itself, and stays tracked as
+/// `Cancelling` until then.
+struct RunningJob {
+    info: DownloadJobInfo,
+    destination: String,
+    token: CancellationToken,
+}
+
+struct DownloadQueue {
+    max_parallel: usize,
+    pending: VecDeque<QueuedJob>,
+    running: Vec<RunningJob>,
+    /// Profiles snapshotted during the current run. A run lasts until the
+    /// queue is empty again, so each profile gets one snapshot per run.
+    snapshotted: HashSet<PathBuf>,
+}
+
+impl Default for DownloadQueue {
+    fn default() -> Self {
+        Self {
+            max_parallel: DEFAULT_MAX_PARALLEL,
+            pending: VecDeque::new(),
+            running: Vec::new(),
+            snapshotted: HashSet::new(),
+        }
+    }
+}
+
+static DOWNLOAD_QUEUE: LazyLock<Mutex<DownloadQueue>> =
+    LazyLock::new(|| Mutex::new(DownloadQueue::default()));
+
+impl DownloadRequest {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Mod { .. } => "mod",
+            Self::Bepinex { .. } => "bepinex",
+        }
+    }
+
+    fn mod_id(&self) -> Option<&str> {
+        match self {
+            Self::Mod { download, .. } => Some(&download.mod_id),
+            Self::Bepinex { .. } => None,
+        }
+    }
+
+    /// Jobs writing to the same destination share temp and backup paths, so
+    /// they never run side by side.
+    fn destination(&self) -> &str {
+        match self {
+            Self::Mod { download, .. } => &download.destination,
+            Self::Bepinex { install, .. } => &install.destination,
+        }
+    }
+
+    /// BepInEx installs take their own snapshot before reinstalling.
+    fn snapshot_profile(&self) -> Option<PathBuf> {
+        match self {
+            Self::Mod { download, .. } => mod_download_service::target_profile_dir(
+                &download.destination,
+                download.lock.as_ref(),
+            ),
+            Self::Bepinex { .. } => None,
+        }
+    }
+}
+
+impl DownloadQueue {
+    fn jobs(&self) -> Vec<DownloadJobInfo> {
+        self.running
+            .iter()
+            .map(|job| job.info.clone())
+            .chain(self.pending.iter().map(|job| job.info.clone()))
+            .collect()
+    }
+
+    fn is_idle(&self) -> bool {
+        self.pending.is_empty() && self.running.is_empty()
+    }
+
+    fn end_run_if_idle(&mut self) {
+        if self.is_idle() {
+            self.snapshotted.clear();
+        }
+    }
+
+    fn payload(&self) -> DownloadQueuePayload {
+        DownloadQueuePayload {
+            max_parallel: self.max_parallel,
+            jobs: self.jobs(),
+        }
+    }
+
+    /// Removes pending jobs matching `predicate` and signals matching running
+    /// ones to stop. Running jobs stay tracked until their task ends, so they
+    /// keep their slot and their destination. Returns the removed pending jobs
+    /// and the number of running jobs signalled.
+    fn cancel_where(
+        &mut self,
+        predicate: impl Fn(&DownloadJobInfo) -> bool,
+    ) -> (Vec<DownloadJobInfo>, usize) {
+        let mut removed = Vec::new();
+
+        self.pending.retain(|job| {
+            if predicate(&job.info) {
+                removed.push(job.info.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        let mut signalled = 0;
+        for job in &mut self.running {
+            if job.info.status == DownloadJobStatus::Cancelling || !predicate(&job.info) {
+                continue;
+            }
+            job.token.cancel();
+            job.info.status = DownloadJobStatus::Cancelling;
+            signalled += 1;
+        }
+
+        (removed, signalled)
+    }
+
+    /// Takes the next pending job that may start now: there must be a free
+    /// slot, and no running or cancelling job may write to its destination.
+    fn next_startable(&mut self) -> Option<QueuedJob> {
+        if self.running.len() >= self.max_parallel {
+            return None;
+        }
+        let index = self.pending.iter().position(|job| {
+            let destination = job.request.destination();
+            !self
+                .running
+                .iter()
+                .any(|running| running.destination == destination)
+        })?;
+        self.pending.remove(index)
+    }
+}
+
+fn lock_queue() -> AppResult<std::sync::MutexGuard<'static, DownloadQueue>> {
+    DOWNLOAD_QUEUE
+        .lock()
+        .map_err(|_| AppError::state("Failed to acquire download queue lock"))
+}
+
+fn emit_queue_snapshot<R: Runtime>(app: &AppHandle<R>, queue: &DownloadQueue) {
+    let _ = app.emit("download-queue-changed", queue.payload());
+}
+
+fn emit_job_finished<R: Runtime>(
+    app: &AppHandle<R>,
+    info: &DownloadJobInfo,
+    outcome: &str,
+    error: Option<String>,
+) {
+    let _ = app.emit(
+        "download-job-finished",
+        DownloadJobFinishedPayload {
+            job_id: info.job_id.clone(),
+            mod_id: info.mod_id.clone(),
+            outcome: outcome.to_string(),
+            error,
+        },
+    );
+}
+
+async fn run_request<R: Runtime>(
+    app: AppHandle<R>,
+    job_id: String,
+    request: DownloadRequest,
+    token: CancellationToken,
+) -> AppResult<()> {
+    match request {
+        DownloadRequest::Mod {
+            download,
+            max_attempts,
+        } => {
+            mod_download_service::download_mod(
+                app,
+                download,
+                RetryPolicy::with_max_attempts(max_attempts),
+                Some(job_id),
+                &token,
+            )
+            .await
+        }
+        DownloadRequest::Bepinex {
+            install,
+            max_attempts,
+        } => {
+            bepinex_service::install_bepinex(
+                app,
+                install,
+                RetryPolicy::with_max_attempts(max_attempts),
+                Some(job_id),
+                &token,
+            )
+            .await
+        }
+    }
+}
+
+fn finish_job<R: Runtime>(app: &AppHandle<R>, job_id: &str, result: AppResult<()>) {
+    let Ok(mut queue) = lock_queue() else {
+        error!("Failed to acquire download queue lock");
+        return;
+    };
+
+    let Some(index) = queue
+        .running
+        .iter()
+        .position(|job| job.info.job_id == job_id)
+    else {
+        return;
+    };
+    let finished = queue.running.swap_remove(index);
+
+    match result {
+        Ok(()) => {
+            info!("Download job {} completed", job_id);
+            emit_job_finished(app, &finished.info, "completed", None);
+        }
+        Err(_) if finished.info.status == DownloadJobStatus::Cancelling => {
+            info!("Download job {} cancelled", job_id);
+            emit_job_finished(app, &finished.info, "cancelled", None);
+        }
+        Err(e) => {
+            warn!("Download job {} failed: {}", job_id, e);
+            emit_job_finished(app, &finished.info, "failed", Some(e.to_string()));
+        }
+    }
+
+    start_pending_jobs(app, &mut queue);
+    queue.end_run_if_idle();
+    emit_queue_snapshot(app, &queue);
+}
+
+fn start_pending_jobs<R: Runtime>(app: &AppHandle<R>, queue: &mut DownloadQueue) {
+    while let Some(QueuedJob { mut info, request }) = queue.next_startable() {
+        info.status = DownloadJobStatus::Running;
+        let job_id = info.job_id.clone();
+        let token = CancellationToken::default();
+        queue.running.push(RunningJob {
+            info,
+            destination: request.destination().to_string(),
+            token: token.clone(),
+        });
+
+        let task_app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = run_request(task_app.clone(), job_id.clone(), request, token).await;
+            finish_job(&task_app, &job_id, result);
+        });
+    }
+}
+
+pub fn enqueue<R: Runtime>(
+    app: &AppHandle<R>,
+    request: DownloadRequest,
+    priority: bool,
+) -> AppResult<String> {
+    // The snapshot is taken before the job is queued so no download of this
+    // run can start ahead of it, and outside the lock so the queue keeps moving.
+    if let Some(profile_dir) = request.snapshot_profile() {
+        let first_in_run = lock_queue()?.snapshotted.insert(profile_dir.clone());
+        if first_in_run {
+            snapshot_service::snapshot_before_batch(
+                app,
+                &profile_dir,
+                SnapshotReason::BeforeModInstall,
+            );
+        }
+    }
+
+    let mut queue = lock_queue()?;
+
+    let job_id = Uuid::new_v4().to_string();
+    let job = QueuedJob {
+        info: DownloadJobInfo {
+            job_id: job_id.clone(),
+            kind: request.kind().to_string(),
+            mod_id: request.mod_id().map(str::to_string),
+            status: DownloadJobStatus::Queued,
+        },
+        request,
+    };
+
+    if priority {
+        queue.pending.push_front(job);
+    } else {
+        queue.pending.push_back(job);
+    }
+
+    start_pending_jobs(app, &mut queue);
+    emit_queue_snapshot(app, &queue);
+    Ok(job_id)
+}
+
+pub fn cancel<R: Runtime>(
+    app: &AppHandle<R>,
+    job_id: Option<&str>,
+    mod_id: Option<&str>,
+) -> AppResult<usize> {
+    if job_id.is_none() && mod_id.is_none() {
+        return Err(AppError::validation(
+            "Either a job id or a mod id is required to cancel a download",
+        ));
+    }
+
+    let mut queue = lock_queue()?;
+    let (removed, signalled) = queue.cancel_where(|info| {
+        job_id.is_some_and(|id| info.job_id == id)
+            || mod_id.is_some_and(|id| info.mod_id.as_deref() == Some(id))
+    });
+    finish_cancelled(app, &mut queue, &removed);
+    Ok(removed.len() + signalled)
+}
+
+pub fn cancel_all<R: Runtime>(app: &AppHandle<R>) -> AppResult<usize> {
+    let mut queue = lock_queue()?;
+    let (removed, signalled) = queue.cancel_where(|_| true);
+    finish_cancelled(app, &mut queue, &removed);
+    Ok(removed.len() + signalled)
+}
+
+/// Reports pending jobs removed by a cancel. Signalled running jobs are
+/// reported by [`finish_job`] once their task ends.
+fn finish_cancelled<R: Runtime>(
+    app: &AppHandle<R>,
+    queue: &mut DownloadQueue,
+    removed: &[DownloadJobInfo],
+) {
+    for info in removed {
+        info!("Download job {} cancelled", info.job_id);
+        emit_job_finished(app, info, "cancelled", None);
+    }
+    start_pending_jobs(app, queue);
+    queue.end_run_if_idle();
+    emit_queue_snapshot(app, queue);
+}
+
+pub fn list_jobs() -> AppResult<DownloadQueuePayload> {
+    Ok(lock_queue()?.payload())
+}
+
+pub fn set_max_parallel<R: Runtime>(app: &AppHandle<R>, max_parallel: usize) -> AppResult<()> {
+    let mut queue = lock_queue()?;
+    queue.max_parallel = max_parallel.max(1);
+    start_pending_jobs(app, &mut queue);
+    emit_queue_snapshot(app, &queue);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(job_id: &str, mod_id: Option<&str>) -> QueuedJob {
+        queued_to(job_id, mod_id, "")
+    }
+
+    fn queued_to(job_id: &str, mod_id: Option<&str>, destination: &str) -> QueuedJob {
+        QueuedJob {
+            info: DownloadJobInfo {
+                job_id: job_id.to_string(),
+                kind: "mod".to_string(),
+                mod_id: mod_id.map(str::to_string),
+                status: DownloadJobStatus::Queued,
+            },
+            request: DownloadRequest::Bepinex {
+                install: BepInExInstallRequest {
+                    url: String::new(),
+                    destination: destination.to_string(),
+                    cache_path: None,
+                    extraction_policy: Default::default(),
+                },
+                max_attempts: None,
+            },
+        }
+    }
+
+    #[test]
+    fn cancel_removes_matching_pending_jobs() {
+        let mut queue = DownloadQueue::default();
+        queue.pending.push_back(queued("a", Some("m1")));
+        queue.pending.push_back(queued("b", Some("m2")));
+        queue.pending.push_back(queued("c", Some("m1")));
+
+        let (removed, signalled) = queue.cancel_where(|info| info.mod_id.as_deref() == Some("m1"));
+        assert_eq!(removed.len(), 2);
+        assert_eq!(signalled, 0);
+        assert_eq!(queue.jobs().len(), 1);
+        assert_eq!(queue.jobs()[0].job_id, "b");
+    }
+
+    fn running(job_id: &str, mod_id: Option<&str>, destination: &str) -> RunningJob {
+        let mut info = queued(job_id, mod_id).info;
+        info.status = DownloadJobStatus::Running;
+        RunningJob {
+            info,
+            destination: destination.to_string(),
+            token: CancellationToken::default(),
+        }
+    }
+
+    #[test]
+    fn cancel_signals_running_jobs_and_keeps_them_tracked() {
+        let mut queue = DownloadQueue::default();
+        queue.running.push(running("a", Some("m1"), "mods/m1"));
+        let token = queue.running[0].token.clone();
+
+        let (removed, signalled) = queue.cancel_where(|_| true);
+        assert!(removed.is_empty());
+        assert_eq!(signalled, 1);
+        assert!(token.is_cancelled());
+        assert_eq!(queue.running.len(), 1);
+        assert!(queue.running[0].info.status == DownloadJobStatus::Cancelling);
+
+        let (_, signalled) = queue.cancel_where(|_| true);
+        assert_eq!(signalled, 0);
+    }
+
+    #[test]
+    fn re_enqueued_mod_waits_for_its_cancelled_job() {
+        let mut queue = DownloadQueue::default();
+        queue.running.push(running("a", Some("m1"), "mods/m1"));
+        queue.cancel_where(|_| true);
+
+        queue
+            .pending
+            .push_back(queued_to("b", Some("m1"), "mods/m1"));
+        queue
+            .pending
+            .push_back(queued_to("c", Some("m2"), "mods/m2"));
+
+        let started = queue.next_startable().unwrap();
+        assert_eq!(started.info.job_id, "c");
+        queue.running.push(running("c", Some("m2"), "mods/m2"));
+        assert!(queue.next_startable().is_none());
+
+        queue.running.retain(|job| job.info.job_id != "a");
+        let started = queue.next_startable().unwrap();
+        assert_eq!(started.info.job_id, "b");
+    }
+
+    #[test]
+    fn cancelling_jobs_keep_their_slot() {
+        let mut queue = DownloadQueue {
+            max_parallel: 1,
+            ..DownloadQueue::default()
+        };
+        queue.running.push(running("a", Some("m1"), "mods/m1"));
+        queue.cancel_where(|_| true);
+        queue
+            .pending
+            .push_back(queued_to("b", Some("m2"), "mods/m2"));
+
+        assert!(queue.next_startable().is_none());
+        assert!(!queue.is_idle());
+    }
+
+    #[test]
+    fn snapshot_run_ends_when_the_queue_drains() {
+        let mut queue = DownloadQueue::default();
+        queue.snapshotted.insert(PathBuf::from("profile"));
+        queue.pending.push_back(queued("a", Some("m1")));
+
+        queue.end_run_if_idle();
+        assert_eq!(queue.snapshotted.len(), 1);
+
+        queue.cancel_where(|_| true);
+        queue.end_run_if_idle();
+        assert!(queue.snapshotted.is_empty());
+    }
+}
@@ -1,30 +1,183 @@
 use crate::backend::error::{AppError, AppResult};
+use crate::backend::services::mod_cache_service;
+use crate::backend::services::play_time_service::{self, PlaySession};
+use crate::backend::services::process_scan_service::{self, GAME_PROCESS_NAME};
 use log::{debug, error, info, warn};
 use std::collections::HashMap;
 use std::process::Child;
 use std::sync::{LazyLock, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Runtime};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameLaunchKind {
+    Modded,
+    Vanilla,
+    Uwp,
+}
+
+/// What a launcher knows about the instance it just started.
+#[derive(Clone, Debug)]
+pub struct GameLaunch {
+    pub kind: GameLaunchKind,
+    pub platform: String,
+    pub profile_id: Option<String>,
+}
+
+struct TrackedLaunch {
+    launch: GameLaunch,
+    launched_at: u64,
+}
+
+impl TrackedLaunch {
+    fn new(launch: GameLaunch) -> Self {
+        Self {
+            launch,
+            launched_at: now_millis(),
+        }
+    }
+
+    fn profile_id(&self) -> Option<&str> {
+        self.launch.profile_id.as_deref()
+    }
+
+    fn to_instance(&self, pid: Option<u32>) -> GameInstance {
+        GameInstance {
+            pid,
+            profile_id: self.launch.profile_id.clone(),
+            platform: self.launch.platform.clone(),
+            kind: self.launch.kind,
+            launched_at: self.launched_at,
+        }
+    }
+}
+
 struct TrackedGameProcess {
     child: Child,
-    profile_id: Option<String>,
+    launch: TrackedLaunch,
+}
+
+/// A game process started on our behalf by another program, e.g. the Steam
+/// client, so there is no `Child` handle to wait on.
+struct ExternalGameProcess {
+    pid: u32,
+    launch: TrackedLaunch,
 }
 
 #[derive(Default)]
 struct TrackedState {
     processes: Vec<TrackedGameProcess>,
-    uwp_instances: Vec<Option<String>>,
+    external_processes: Vec<ExternalGameProcess>,
+    uwp_instances: Vec<TrackedLaunch>,
+    /// Per profile, the end of the time already added to its play time.
+    play_time_counted_until: HashMap<String, u64>,
 }
 
+/// How long a game gets to close after a stop request before it is killed.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(10);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
 static TRACKED_STATE: LazyLock<Mutex<TrackedState>> =
     LazyLock::new(|| Mutex::new(TrackedState::default()));
 
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct GameInstance {
+    /// Unknown for Xbox instances, which are started through the shell.
+    pub pid: Option<u32>,
+    pub profile_id: Option<String>,
+    pub platform: String,
+    pub kind: GameLaunchKind,
+    /// Unix time in milliseconds; clients derive the elapsed time from it.
+    pub launched_at: u64,
+}
+
 #[derive(Clone, serde::Serialize)]
 pub struct GameStatePayload {
     pub running: bool,
     pub running_count: usize,
     pub profile_instance_counts: HashMap<String, usize>,
+    pub instances: Vec<GameInstance>,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct GameStopResult {
+    pub pid: u32,
+    pub profile_id: Option<String>,
+    /// Whether the process ignored the stop request and had to be killed.
+    pub forced: bool,
+}
+
+/// A process taken out of the tracked state so it can be stopped.
+enum StopTarget {
+    Launched(TrackedGameProcess),
+    External(ExternalGameProcess),
+}
+
+impl StopTarget {
+    fn pid(&self) -> u32 {
+        match self {
+            Self::Launched(tracked) => tracked.child.id(),
+            Self::External(tracked) => tracked.pid,
+        }
+    }
+
+    fn profile_id(&self) -> Option<String> {
+        match self {
+            Self::Launched(tracked) => tracked.launch.launch.profile_id.clone(),
+            Self::External(tracked) => tracked.launch.launch.profile_id.clone(),
+        }
+    }
+
+    fn has_exited(&mut self) -> bool {
+        match self {
+            Self::Launched(tracked) => !matches!(tracked.child.try_wait(), Ok(None)),
+            Self::External(tracked) => {
+                !process_scan_service::is_running_as(tracked.pid, GAME_PROCESS_NAME)
+            }
+        }
+    }
+
+    /// Launched games lead their own process group on Unix, see
+    /// `launch_service`, so the whole group is signalled.
+    fn request_exit(&mut self) -> bool {
+        match self {
+            #[cfg(unix)]
+            Self::Launched(tracked) => process_scan_service::request_group_exit(tracked.child.id()),
+            #[cfg(not(unix))]
+            Self::Launched(tracked) => process_scan_service::request_exit(tracked.child.id()),
+            Self::External(tracked) => {
+                process_scan_service::is_running_as(tracked.pid, GAME_PROCESS_NAME)
+                    && process_scan_service::request_exit(tracked.pid)
+            }
+        }
+    }
+
+    fn kill(&mut self) {
+        let killed = match self {
+            #[cfg(unix)]
+            Self::Launched(tracked) => {
+                process_scan_service::force_group_exit(tracked.child.id())
+                    || tracked.child.kill().is_ok()
+            }
+            #[cfg(not(unix))]
+            Self::Launched(tracked) => tracked.child.kill().is_ok(),
+            Self::External(tracked) => {
+                !process_scan_service::is_running_as(tracked.pid, GAME_PROCESS_NAME)
+                    || process_scan_service::force_exit(tracked.pid)
+            }
+        };
+        if !killed {
+            warn!("Failed to kill game process {}", self.pid());
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
 }
 
 fn reap_process(mut child: Child) {
@@ -33,34 +186,168 @@ fn reap_process(mut child: Child) {
     }
 }
 
+fn collect_instances(state: &TrackedState) -> Vec<GameInstance> {
+    let mut instances: Vec<GameInstance> = state
+        .processes
+        .iter()
+        .map(|tracked| tracked.launch.to_instance(Some(tracked.child.id())))
+        .chain(
+            state
+                .external_processes
+                .iter()
+                .map(|tracked| tracked.launch.to_instance(Some(tracked.pid))),
+        )
+        .chain(
+            state
+                .uwp_instances
+                .iter()
+                .map(|launch| launch.to_instance(None)),
+        )
+        .collect();
+    instances.sort_by_key(|instance| instance.launched_at);
+    instances
+}
+
 fn build_state_payload(state: &TrackedState) -> GameStatePayload {
+    let instances = collect_instances(state);
     let mut profile_instance_counts = HashMap::new();
-    for tracked in &state.processes {
-        if let Some(profile_id) = &tracked.profile_id {
-            *profile_instance_counts
-                .entry(profile_id.clone())
-                .or_insert(0) += 1;
-        }
-    }
-    for profile_id in state.uwp_instances.iter().flatten() {
+    for profile_id in instances
+        .iter()
+        .filter_map(|instance| instance.profile_id.as_ref())
+    {
         *profile_instance_counts
             .entry(profile_id.clone())
             .or_insert(0) += 1;
     }
 
-    let running_count = state.processes.len() + state.uwp_instances.len();
     GameStatePayload {
-        running: running_count > 0,
-        running_count,
+        running: !instances.is_empty(),
+        running_count: instances.len(),
         profile_instance_counts,
+        instances,
     }
 }
 
-fn emit_state_snapshot<R: Runtime>(app: &AppHandle<R>, state: &TrackedState) {
-    let payload = build_state_payload(state);
+pub fn list_instances() -> AppResult<Vec<GameInstance>> {
+    let state = TRACKED_STATE
+        .lock()
+        .map_err(|_| AppError::state("Failed to acquire game process lock"))?;
+    Ok(collect_instances(&state))
+}
+
+fn emit_state(app: &AppHandle<impl Runtime>, payload: GameStatePayload) {
     let _ = app.emit("game-state-changed", payload);
 }
 
+/// Metadata updates for a state change. They are collected while
+/// `TRACKED_STATE` is locked and written once it has been released, so file
+/// I/O never blocks the other monitors.
+#[derive(Default)]
+struct PendingWrites {
+    launch: Option<(String, u64)>,
+    sessions: Vec<(String, PlaySession)>,
+}
+
+impl PendingWrites {
+    fn launch(launch: &TrackedLaunch) -> Self {
+        Self {
+            launch: launch
+                .profile_id()
+                .map(|profile_id| (profile_id.to_string(), launch.launched_at)),
+            sessions: Vec::new(),
+        }
+    }
+
+    /// Builds the session of an instance that has just left the tracked state.
+    fn end_session(&mut self, state: &mut TrackedState, launch: &TrackedLaunch) {
+        self.sessions
+            .extend(finish_session(state, launch, now_millis()));
+    }
+
+    fn persist<R: Runtime>(self, app: &AppHandle<R>) {
+        if self.launch.is_none() && self.sessions.is_empty() {
+            return;
+        }
+        let profiles_dir = match mod_cache_service::profiles_dir(app) {
+            Ok(profiles_dir) => profiles_dir,
+            Err(e) => {
+                warn!("Failed to record play time: {}", e);
+                return;
+            }
+        };
+
+        for (profile_id, session) in self.sessions {
+            match play_time_service::record_session(&profiles_dir, &profile_id, &session) {
+                Ok(_) => info!(
+                    "Recorded {} ms of play time for profile {}",
+                    session.counted_ms, profile_id
+                ),
+                Err(e) => warn!(
+                    "Failed to record play session for profile {}: {}",
+                    profile_id, e
+                ),
+            }
+        }
+        if let Some((profile_id, launched_at)) = self.launch
+            && let Err(e) =
+                play_time_service::record_launch(&profiles_dir, &profile_id, launched_at)
+        {
+            warn!("Failed to record launch of profile {}: {}", profile_id, e);
+        }
+    }
+}
+
+/// Play time counts wall-clock time with at least one instance of the profile
+/// running, so overlapping instances do not add up. Xbox instances are never
+/// seen exiting and are left out.
+fn finish_session(
+    state: &mut TrackedState,
+    launch: &TrackedLaunch,
+    ended_at: u64,
+) -> Option<(String, PlaySession)> {
+    let profile_id = launch.profile_id()?.to_string();
+    let earliest_running = state
+        .processes
+        .iter()
+        .map(|tracked| &tracked.launch)
+        .chain(
+            state
+                .external_processes
+                .iter()
+                .map(|tracked| &tracked.launch),
+        )
+        .filter(|other| other.profile_id() == Some(profile_id.as_str()))
+        .map(|other| other.launched_at)
+        .min();
+
+    let counted_until = state
+        .play_time_counted_until
+        .get(&profile_id)
+        .copied()
+        .unwrap_or(0);
+    let counted_from = earliest_running
+        .map_or(launch.launched_at, |started| {
+            started.min(launch.launched_at)
+        })
+        .max(counted_until);
+    if earliest_running.is_some() {
+        state
+            .play_time_counted_until
+            .insert(profile_id.clone(), ended_at.max(counted_until));
+    } else {
+        state.play_time_counted_until.remove(&profile_id);
+    }
+
+    let session = PlaySession {
+        started_at: launch.launched_at,
+        ended_at,
+        duration_ms: ended_at.saturating_sub(launch.launched_at),
+        counted_ms: ended_at.saturating_sub(counted_from),
+        platform: launch.launch.platform.clone(),
+    };
+    Some((profile_id, session))
+}
+
 fn monitor_game_process<R: Runtime>(app: AppHandle<R>, process_id: u32) {
     std::thread::spawn(move || {
         info!("Monitoring game process state");
@@ -82,22 +369,20 @@ fn monitor_game_process<R: Runtime>(app: AppHandle<R>, process_id: u32) {
             };
 
             match state.processes[index].child.try_wait() {
-                Ok(Some(status)) => {
-                    info!("Game process exited with status: {:?}", status);
-                    let tracked = state.processes.swap_remove(index);
-                    reap_process(tracked.child);
-                    emit_state_snapshot(&app, &state);
-                    break;
-                }
-                Ok(None) => {}
-                Err(e) => {
-                    warn!("Failed to check game process state: {}", e);
-                    let tracked = state.processes.swap_remove(index);
-                    reap_process(tracked.child);
-                    emit_state_snapshot(&app, &state);
-                    break;
-                }
+                Ok(Some(status)) => info!("Game process exited with status: {:?}", status),
+                Ok(None) => continue,
+                Err(e) => warn!("Failed to check game process state: {}", e),
             }
+
+            let tracked = state.processes.swap_remove(index);
+            reap_process(tracked.child);
+            let mut writes = PendingWrites::default();
+            writes.end_session(&mut state, &tracked.launch);
+            let payload = build_state_payload(&state);
+            drop(state);
+            writes.persist(&app);
+            emit_state(&app, payload);
+            break;
         }
     });
 }
@@ -105,10 +390,12 @@ fn monitor_game_process<R: Runtime>(app: AppHandle<R>, process_id: u32) {
 pub fn register_launched_process<R: Runtime>(
     app: AppHandle<R>,
     child: Child,
-    profile_id: Option<String>,
+    launch: GameLaunch,
 ) -> AppResult<()> {
-    let process_id: u32;
-    {
+    let process_id = child.id();
+    let launch = TrackedLaunch::new(launch);
+    let mut writes = PendingWrites::launch(&launch);
+    let payload = {
         let mut state = TRACKED_STATE
             .lock()
             .map_err(|_| AppError::state("Failed to acquire game process lock"))?;
@@ -116,57 +403,354 @@ pub fn register_launched_process<R: Runtime>(
         let mut i = 0;
         while i < state.processes.len() {
             match state.processes[i].child.try_wait() {
-                Ok(Some(_)) => {
-                    let tracked = state.processes.swap_remove(i);
-                    reap_process(tracked.child);
-                }
-                Ok(None) => i += 1,
-                Err(e) => {
-                    warn!("Failed to check tracked process state: {}", e);
-                    let tracked = state.processes.swap_remove(i);
-                    reap_process(tracked.child);
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    i += 1;
+                    continue;
                 }
+                Err(e) => warn!("Failed to check tracked process state: {}", e),
             }
+            let tracked = state.processes.swap_remove(i);
+            reap_process(tracked.child);
+            writes.end_session(&mut state, &tracked.launch);
         }
 
-        process_id = child.id();
-        state
-            .processes
-            .push(TrackedGameProcess { child, profile_id });
-        emit_state_snapshot(&app, &state);
-    }
+        state.processes.push(TrackedGameProcess { child, launch });
+        build_state_payload(&state)
+    };
+    writes.persist(&app);
+    emit_state(&app, payload);
 
     monitor_game_process(app, process_id);
     Ok(())
 }
 
+fn monitor_external_process<R: Runtime>(app: AppHandle<R>, pid: u32) {
+    std::thread::spawn(move || {
+        info!("Monitoring external game process {}", pid);
+        loop {
+            std::thread::sleep(Duration::from_secs(1));
+            if process_scan_service::is_process_running(pid) {
+                continue;
+            }
+
+            info!("External game process {} exited", pid);
+            let Ok(mut state) = TRACKED_STATE.lock() else {
+                error!("Failed to acquire game process lock");
+                break;
+            };
+            // Already removed when the process was stopped from here.
+            if let Some(index) = state
+                .external_processes
+                .iter()
+                .position(|tracked| tracked.pid == pid)
+            {
+                let tracked = state.external_processes.swap_remove(index);
+                let mut writes = PendingWrites::default();
+                writes.end_session(&mut state, &tracked.launch);
+                let payload = build_state_payload(&state);
+                drop(state);
+                writes.persist(&app);
+                emit_state(&app, payload);
+            }
+            break;
+        }
+    });
+}
+
+pub fn register_external_process<R: Runtime>(
+    app: AppHandle<R>,
+    pid: u32,
+    launch: GameLaunch,
+) -> AppResult<()> {
+    let launch = TrackedLaunch::new(launch);
+    let writes = PendingWrites::launch(&launch);
+    let payload = {
+        let mut state = TRACKED_STATE
+            .lock()
+            .map_err(|_| AppError::state("Failed to acquire game process lock"))?;
+        let already_tracked = state
+            .processes
+            .iter()
+            .any(|tracked| tracked.child.id() == pid)
+            || state
+                .external_processes
+                .iter()
+                .any(|tracked| tracked.pid == pid);
+        if already_tracked {
+            return Ok(());
+        }
+
+        state
+            .external_processes
+            .push(ExternalGameProcess { pid, launch });
+        build_state_payload(&state)
+    };
+    writes.persist(&app);
+    emit_state(&app, payload);
+
+    monitor_external_process(app, pid);
+    Ok(())
+}
+
 pub fn register_uwp_instance<R: Runtime>(
     app: &AppHandle<R>,
     profile_id: Option<String>,
 ) -> AppResult<()> {
-    let mut state = TRACKED_STATE
-        .lock()
-        .map_err(|_| AppError::state("Failed to update game state"))?;
-    state.uwp_instances.push(profile_id);
-    emit_state_snapshot(app, &state);
+    let launch = TrackedLaunch::new(GameLaunch {
+        kind: GameLaunchKind::Uwp,
+        platform: "xbox".to_string(),
+        profile_id,
+    });
+    let writes = PendingWrites::launch(&launch);
+    let payload = {
+        let mut state = TRACKED_STATE
+            .lock()
+            .map_err(|_| AppError::state("Failed to update game state"))?;
+        state.uwp_instances.push(launch);
+        build_state_payload(&state)
+    };
+    writes.persist(app);
+    emit_state(app, payload);
     Ok(())
 }
 
+/// Removes matching processes from the tracked state. Their monitor threads
+/// stop once the entries are gone.
+fn take_stop_targets(
+    state: &mut TrackedState,
+    matches: impl Fn(u32, Option<&str>) -> bool,
+) -> Vec<StopTarget> {
+    let mut targets = Vec::new();
+
+    let mut i = 0;
+    while i < state.processes.len() {
+        let tracked = &state.processes[i];
+        if matches(tracked.child.id(), tracked.launch.profile_id()) {
+            targets.push(StopTarget::Launched(state.processes.swap_remove(i)));
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut i = 0;
+    while i < state.external_processes.len() {
+        let tracked = &state.external_processes[i];
+        if matches(tracked.pid, tracked.launch.profile_id()) {
+            targets.push(StopTarget::External(
+                state.external_processes.swap_remove(i),
+            ));
+        } else {
+            i += 1;
+        }
+    }
+
+    targets
+}
+
+/// Asks every target to exit, waits up to `grace_period` and kills whatever
+/// is still running.
+fn terminate(
+    mut targets: Vec<StopTarget>,
+    grace_period: Duration,
+) -> Vec<(GameStopResult, TrackedLaunch)> {
+    for target in &mut targets {
+        if !target.has_exited() && !target.request_exit() {
+            debug!(
+                "Stop request for game process {} was not delivered",
+                target.pid()
+            );
+        }
+    }
+
+    let deadline = Instant::now() + grace_period;
+    let mut exited = vec![false; targets.len()];
+    loop {
+        for (target, exited) in targets.iter_mut().zip(exited.iter_mut()) {
+            if !*exited {
+                *exited = target.has_exited();
+            }
+        }
+        if exited.iter().all(|exited| *exited) || Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(STOP_POLL_INTERVAL);
+    }
+
+    targets
+        .into_iter()
+        .zip(exited)
+        .map(|(mut target, exited)| {
+            if !exited {
+                info!(
+                    "Game process {} did not exit in time, killing it",
+                    target.pid()
+                );
+                target.kill();
+            }
+            let result = GameStopResult {
+                pid: target.pid(),
+                profile_id: target.profile_id(),
+                forced: !exited,
+            };
+            let launch = match target {
+                StopTarget::Launched(tracked) => {
+                    reap_process(tracked.child);
+                    tracked.launch
+                }
+                StopTarget::External(tracked) => tracked.launch,
+            };
+            (result, launch)
+        })
+        .collect()
+}
+
+fn stop_matching<R: Runtime>(
+    app: &AppHandle<R>,
+    matches: impl Fn(u32, Option<&str>) -> bool,
+) -> AppResult<Vec<GameStopResult>> {
+    let targets = {
+        let mut state = TRACKED_STATE
+            .lock()
+            .map_err(|_| AppError::state("Failed to acquire game process lock"))?;
+        take_stop_targets(&mut state, matches)
+    };
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let stopped = terminate(targets, STOP_GRACE_PERIOD);
+    let mut writes = PendingWrites::default();
+    let mut results = Vec::with_capacity(stopped.len());
+    let payload = {
+        let mut state = TRACKED_STATE
+            .lock()
+            .map_err(|_| AppError::state("Failed to acquire game process lock"))?;
+        for (result, launch) in stopped {
+            writes.end_session(&mut state, &launch);
+            results.push(result);
+        }
+        build_state_payload(&state)
+    };
+    writes.persist(app);
+    emit_state(app, payload);
+    Ok(results)
+}
+
+/// Stops one tracked game process. Blocks for up to the grace period.
+pub fn stop_game<R: Runtime>(app: &AppHandle<R>, pid: u32) -> AppResult<GameStopResult> {
+    stop_matching(app, |candidate, _| candidate == pid)?
+        .pop()
+        .ok_or_else(|| AppError::validation(format!("No running game instance with PID {pid}")))
+}
+
+/// Stops every tracked game process, or only those of `profile_id`. Xbox
+/// instances are started through the shell without a known PID, so they are
+/// left alone.
+pub fn stop_all_games<R: Runtime>(
+    app: &AppHandle<R>,
+    profile_id: Option<&str>,
+) -> AppResult<Vec<GameStopResult>> {
+    stop_matching(app, |_, candidate| {
+        profile_id.is_none_or(|profile_id| candidate == Some(profile_id))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn uwp_launch(profile_id: &str) -> TrackedLaunch {
+        TrackedLaunch::new(GameLaunch {
+            kind: GameLaunchKind::Uwp,
+            platform: "xbox".to_string(),
+            profile_id: Some(profile_id.to_string()),
+        })
+    }
+
     #[test]
     fn payload_aggregates_profile_counts() {
         let mut state = TrackedState::default();
-        state.uwp_instances.push(Some("p1".to_string()));
-        state.uwp_instances.push(Some("p1".to_string()));
-        state.uwp_instances.push(Some("p2".to_string()));
+        for profile_id in ["p1", "p1", "p2"] {
+            state.uwp_instances.push(uwp_launch(profile_id));
+        }
 
         let payload = build_state_payload(&state);
         assert!(payload.running);
         assert_eq!(payload.running_count, 3);
         assert_eq!(payload.profile_instance_counts.get("p1"), Some(&2));
         assert_eq!(payload.profile_instance_counts.get("p2"), Some(&1));
+        assert_eq!(payload.instances.len(), 3);
+        assert!(
+            payload
+                .instances
+                .iter()
+                .all(|instance| { instance.pid.is_none() && instance.kind == GameLaunchKind::Uwp })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_targets_filter_by_profile_and_terminate() {
+        use std::os::unix::process::CommandExt;
+
+        let mut state = TrackedState::default();
+        for profile_id in ["p1", "p2"] {
+            let child = std::process::Command::new("sleep")
+                .arg("30")
+                .process_group(0)
+                .spawn()
+                .unwrap();
+            state.processes.push(TrackedGameProcess {
+                child,
+                launch: TrackedLaunch::new(GameLaunch {
+                    kind: GameLaunchKind::Modded,
+                    platform: "steam".to_string(),
+                    profile_id: Some(profile_id.to_string()),
+                }),
+            });
+        }
+
+        let targets = take_stop_targets(&mut state, |_, profile_id| profile_id == Some("p1"));
+        assert_eq!(targets.len(), 1);
+        assert_eq!(state.processes.len(), 1);
+
+        let results = terminate(targets, Duration::from_secs(5));
+        assert_eq!(results[0].0.profile_id.as_deref(), Some("p1"));
+        assert!(!results[0].0.forced);
+
+        let remaining = take_stop_targets(&mut state, |_, _| true);
+        let results = terminate(remaining, Duration::ZERO);
+        assert_eq!(results.len(), 1);
+        assert!(state.processes.is_empty());
+    }
+
+    #[test]
+    fn overlapping_sessions_count_once() {
+        let mut state = TrackedState::default();
+        let launch_at = |launched_at: u64| TrackedLaunch {
+            launched_at,
+            ..uwp_launch("p1")
+        };
+        let long = launch_at(0);
+        let short = launch_at(5_000);
+        state.external_processes.push(ExternalGameProcess {
+            pid: 1,
+            launch: launch_at(0),
+        });
+
+        // The short instance ends while the long one is still running.
+        let (_, session) = finish_session(&mut state, &short, 10_000).unwrap();
+        assert_eq!(session.duration_ms, 5_000);
+        assert_eq!(session.counted_ms, 10_000);
+
+        state.external_processes.clear();
+        let (_, session) = finish_session(&mut state, &long, 20_000).unwrap();
+        assert_eq!(session.duration_ms, 20_000);
+        assert_eq!(session.counted_ms, 10_000);
+        assert!(state.play_time_counted_until.is_empty());
+
+        let (_, session) = finish_session(&mut state, &launch_at(30_000), 31_000).unwrap();
+        assert_eq!(session.counted_ms, 1_000);
     }
 }
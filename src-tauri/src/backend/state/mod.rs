@@ -1 +1,3 @@
+pub mod archive_operations;
+pub mod download_queue;
 pub mod game_runtime;
@@ -31,6 +31,14 @@ class GameStateFacade {
 		return gameRuntimeState.getSessionDuration();
 	}
 
+	get instances() {
+		return gameRuntimeState.instances;
+	}
+
+	getProfileInstances(profileId: string) {
+		return gameRuntimeState.getProfileInstances(profileId);
+	}
+
 	init() {
 		return gameRuntimeState.init();
 	}
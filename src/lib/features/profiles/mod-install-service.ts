@@ -35,7 +35,7 @@ class ModInstallService {
 		modId: string,
 		version: string,
 		info: ModVersionInfo
-	): Promise<{ url: string; fileName: string; checksum: string }> {
+	): Promise<{ url: string; fileName: string; checksum: string; platform?: string }> {
 		const legacyPath = `/api/v2/mods/${modId}/versions/${version}/file`;
 		const defaultUrl = info.download_url ?? legacyPath;
 		const defaultTarget = {
@@ -62,7 +62,8 @@ class ModInstallService {
 					entry.download_url ?? `${legacyPath}?platform=windows&arch=${architecture}`
 				),
 				fileName: entry.file_name ?? info.file_name,
-				checksum: entry.checksum ?? info.checksum
+				checksum: entry.checksum ?? info.checksum,
+				platform: `${entry.platform}-${entry.architecture}`
 			};
 		}
 
@@ -153,7 +154,13 @@ class ModInstallService {
 				modId,
 				url: target.url,
 				destination,
-				expectedChecksum: target.checksum
+				expectedChecksum: target.checksum,
+				lock: {
+					profilePath,
+					version,
+					platform: target.platform ?? null,
+					dependencies: info.dependencies
+				}
 			}
 		});
 
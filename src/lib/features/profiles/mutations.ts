@@ -103,8 +103,13 @@ export const profileMutations = {
 	}),
 
 	removeMod: (queryClient: QueryClient) => ({
-		mutationFn: (args: { profileId: string; modId: string }) =>
-			profileWorkflowService.removeModFromProfile(args.profileId, args.modId),
+		mutationFn: async (args: { profileId: string; modId: string }) => {
+			const profile = await profileWorkflowService.getProfileById(args.profileId);
+			if (profile) {
+				await profileWorkflowService.snapshotBeforeModChanges(profile.path, 'before_mod_removal');
+			}
+			await profileWorkflowService.removeModFromProfile(args.profileId, args.modId);
+		},
 		onSuccess: async (_data: void, args: { profileId: string }) => {
 			await invalidateProfileAndDiskQueries(queryClient, args);
 		}
@@ -125,14 +130,6 @@ export const profileMutations = {
 		}
 	}),
 
-	updatePlayTime: (queryClient: QueryClient) => ({
-		mutationFn: (args: { profileId: string; durationMs: number }) =>
-			profileWorkflowService.addPlayTime(args.profileId, args.durationMs),
-		onSuccess: () => {
-			queryClient.invalidateQueries({ queryKey: profilesQueryKey });
-		}
-	}),
-
 	retryBepInExInstall: (queryClient: QueryClient) => ({
 		mutationFn: (args: { profileId: string; profilePath: string }) =>
 			profileWorkflowService.retryBepInExInstall(args.profileId, args.profilePath),
@@ -154,14 +151,6 @@ export const profileMutations = {
 		}
 	}),
 
-	updateLastLaunched: (queryClient: QueryClient) => ({
-		mutationFn: (profileId: string) => profileWorkflowService.updateLastLaunched(profileId),
-		onSuccess: () => {
-			queryClient.invalidateQueries({ queryKey: profilesQueryKey });
-			queryClient.invalidateQueries({ queryKey: profilesActiveQueryKey });
-		}
-	}),
-
 	installMods: (queryClient: QueryClient) => ({
 		mutationFn: async (args: InstallArgs) => {
 			const profile = await profileWorkflowService.getProfileById(args.profileId);
@@ -179,6 +168,8 @@ export const profileMutations = {
 				);
 			}
 
+			await profileWorkflowService.snapshotBeforeModChanges(args.profilePath, 'before_mod_install');
+
 			const installed: InstalledMod[] = [];
 			const persisted: InstalledMod[] = [];
 			try {
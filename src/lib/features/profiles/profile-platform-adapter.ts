@@ -76,6 +76,13 @@ class ProfilePlatformAdapter {
 	importProfileZip(args: { zipPath: string; destination: string }) {
 		return invoke<{ metadata_name?: string | null }>('profiles_import_zip', { args });
 	}
+
+	snapshotBatch(args: {
+		profilePath: string;
+		reason: 'before_mod_install' | 'before_mod_removal';
+	}) {
+		return invoke('profiles_snapshot_batch', { args });
+	}
 }
 
 export const profilePlatformAdapter = new ProfilePlatformAdapter();
@@ -1,14 +1,16 @@
 import { type } from 'arktype';
-import { getStore } from '$lib/state/store';
-import { debug, info, warn } from '@tauri-apps/plugin-log';
+import { invoke } from '@tauri-apps/api/core';
+import { warn } from '@tauri-apps/plugin-log';
 import { ProfileEntry, type Profile } from './schema';
 import { profilePlatformAdapter } from './profile-platform-adapter';
 
 const ProfilesArray = type(ProfileEntry.array());
 
+/**
+ * Profile metadata is owned by the backend: reads, migrations and writes all
+ * go through the `profiles_*` commands so there is a single writer.
+ */
 class ProfileRepository {
-	private migrationDone = false;
-
 	async getProfilesDir(): Promise<string> {
 		const dataDir = await profilePlatformAdapter.getAppDataDir();
 		const profilesDir = await profilePlatformAdapter.joinPath(dataDir, 'profiles');
@@ -16,106 +18,48 @@ class ProfileRepository {
 		return profilesDir;
 	}
 
-	private async getMetadataPath(profileDir: string): Promise<string> {
-		return profilePlatformAdapter.joinPath(profileDir, 'metadata.json');
+	async getProfiles(): Promise<Profile[]> {
+		const result = ProfilesArray(await invoke<unknown>('profiles_list'));
+		if (result instanceof type.errors) {
+			throw new Error(`Invalid profile list: ${result.summary}`);
+		}
+		return result;
 	}
 
-	async readMetadata(profileDir: string): Promise<Profile | null> {
+	async getProfileById(id: string): Promise<Profile | undefined> {
 		try {
-			const metadataPath = await this.getMetadataPath(profileDir);
-			const raw = await profilePlatformAdapter.readJsonFile<Record<string, unknown>>(metadataPath);
-			const result = ProfileEntry({ ...raw, path: profileDir });
+			const raw = await invoke<unknown>('profiles_get', { args: { profileId: id } });
+			const result = ProfileEntry(raw);
 			if (result instanceof type.errors) {
-				warn(`ProfileEntry validation failed for ${profileDir}: ${result.summary}`);
-				return null;
+				warn(`ProfileEntry validation failed for ${id}: ${result.summary}`);
+				return undefined;
 			}
 			return result;
 		} catch (error) {
-			const message = error instanceof Error ? error.message : String(error);
-			warn(`Failed to read metadata for ${profileDir}: ${message}`);
-			return null;
+			warn(`Failed to read profile ${id}: ${error}`);
+			return undefined;
 		}
 	}
 
-	async writeMetadata(profile: Profile): Promise<void> {
-		const metadataPath = await this.getMetadataPath(profile.path);
-		await profilePlatformAdapter.writeJsonFile(metadataPath, profile);
+	createProfile(name: string): Promise<Profile> {
+		return invoke<Profile>('profiles_create', { args: { name } });
 	}
 
-	async getProfiles(): Promise<Profile[]> {
-		const profilesDir = await this.getProfilesDir();
-		let entries: Awaited<ReturnType<typeof profilePlatformAdapter.readDirectory>>;
-		try {
-			entries = await profilePlatformAdapter.readDirectory(profilesDir);
-		} catch {
-			debug('Failed to read profiles directory');
-			return [];
-		}
-
-		let legacyMap: Map<string, Profile> | null = null;
-		if (!this.migrationDone) {
-			const store = await getStore();
-			const rawLegacy = await store.get('profiles');
-			const legacyResult = ProfilesArray(rawLegacy ?? []);
-			const legacyProfiles = legacyResult instanceof type.errors ? [] : legacyResult;
-
-			if (legacyProfiles.length > 0) {
-				legacyMap = new Map(legacyProfiles.map((profile) => [profile.id, profile]));
-			} else {
-				this.migrationDone = true;
-			}
-		}
-
-		let migratedCount = 0;
-		const profiles: Profile[] = [];
-
-		for (const entry of entries) {
-			if (!entry.isDirectory) continue;
-
-			const profileDir = await profilePlatformAdapter.joinPath(profilesDir, entry.name);
-			let profile = await this.readMetadata(profileDir);
-
-			if (!profile && legacyMap) {
-				const legacy = legacyMap.get(entry.name);
-				if (legacy) {
-					profile = { ...legacy, path: profileDir };
-					await this.writeMetadata(profile);
-					migratedCount++;
-					info(`Migrated profile ${entry.name} to metadata.json`);
-				}
-			}
-
-			if (!profile) continue;
-			profiles.push(profile);
-		}
+	writeMetadata(profile: Profile): Promise<Profile> {
+		return invoke<Profile>('profiles_update', { args: { profile } });
+	}
 
-		if (legacyMap && migratedCount > 0) {
-			const allMigrated = [...legacyMap.keys()].every((id) =>
-				profiles.some((profile) => profile.id === id)
-			);
-			if (allMigrated) {
-				const store = await getStore();
-				await store.set('profiles', []);
-				await store.save();
-				this.migrationDone = true;
-				info('All profiles migrated to metadata.json — cleared legacy registry entry');
-			}
-		} else if (legacyMap) {
-			this.migrationDone = true;
-		}
+	renameProfile(id: string, name: string): Promise<Profile> {
+		return invoke<Profile>('profiles_rename', { args: { profileId: id, name } });
+	}
 
-		return profiles.sort((a, b) => {
-			const aLaunched = a.last_launched_at ?? 0;
-			const bLaunched = b.last_launched_at ?? 0;
-			if (aLaunched !== bLaunched) return bLaunched - aLaunched;
-			return b.created_at - a.created_at;
-		});
+	deleteProfile(id: string): Promise<void> {
+		return invoke('profiles_delete', { args: { profileId: id } });
 	}
 
-	async getProfileById(id: string): Promise<Profile | undefined> {
-		const profilesDir = await this.getProfilesDir();
-		const profileDir = await profilePlatformAdapter.joinPath(profilesDir, id);
-		return (await this.readMetadata(profileDir)) ?? undefined;
+	/** Registers a freshly imported profile; the backend checks its schema and BepInEx install. */
+	registerImportedProfile(profile: Profile): Promise<Profile> {
+		return invoke<Profile>('profiles_register_import', { args: { profile } });
 	}
 
 	async createProfileDir(id: string): Promise<string> {
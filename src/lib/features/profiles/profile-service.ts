@@ -56,12 +56,8 @@ class ProfileServiceFacade {
 	readonly updateProfileIcon = (profileId: string, selection: ProfileIconSelection) =>
 		profileWorkflowService.updateProfileIcon(profileId, selection);
 	readonly getActiveProfile = () => profileWorkflowService.getActiveProfile();
-	readonly updateLastLaunched = (profileId: string) =>
-		profileWorkflowService.updateLastLaunched(profileId);
 	readonly addModToProfile = (profileId: string, modId: string, version: string, file: string) =>
 		profileWorkflowService.addModToProfile(profileId, modId, version, file);
-	readonly addPlayTime = (profileId: string, durationMs: number) =>
-		profileWorkflowService.addPlayTime(profileId, durationMs);
 	readonly removeModFromProfile = (profileId: string, modId: string) =>
 		profileWorkflowService.removeModFromProfile(profileId, modId);
 	readonly getModFiles = (profilePath: string) => profileWorkflowService.getModFiles(profilePath);
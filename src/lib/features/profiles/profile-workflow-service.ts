@@ -31,29 +31,8 @@ class ProfileWorkflowService {
 		const trimmed = name.trim();
 		if (!trimmed) throw new Error('Profile name cannot be empty');
 
-		const profiles = await this.getProfiles();
-		if (profiles.some((profile) => profile.name.toLowerCase() === trimmed.toLowerCase())) {
-			throw new Error(`Profile '${trimmed}' already exists`);
-		}
-
-		const timestamp = Date.now();
-		const profileId = this.buildProfileId(trimmed, timestamp);
-		const profilePath = await profileRepository.createProfileDir(profileId);
-
-		const profile: Profile = {
-			id: profileId,
-			name: trimmed,
-			path: profilePath,
-			created_at: timestamp,
-			last_launched_at: undefined,
-			bepinex_installed: false,
-			total_play_time: 0,
-			icon_mode: 'default',
-			mods: []
-		};
-
-		await profileRepository.writeMetadata(profile);
-		this.installBepInExInBackground(profileId, profilePath, hooks).catch((error) => {
+		const profile = await profileRepository.createProfile(trimmed);
+		this.installBepInExInBackground(profile.id, profile.path, hooks).catch((error) => {
 			logError(
 				`installBepInExInBackground failed: ${error instanceof Error ? error.message : error}`
 			);
@@ -93,8 +72,6 @@ class ProfileWorkflowService {
 				path: profilePath,
 				created_at: timestamp,
 				last_launched_at: importedMetadata.lastLaunchedAt,
-				bepinex_installed: true,
-				total_play_time: 0,
 				icon_mode: importedMetadata.iconMode ?? 'default',
 				custom_icon_extension: importedMetadata.customIconExtension,
 				icon_mod_id: importedMetadata.iconModId,
@@ -102,8 +79,7 @@ class ProfileWorkflowService {
 			};
 			this.normalizeProfileIconSelection(profile);
 
-			await profileRepository.writeMetadata(profile);
-			return profile;
+			return await profileRepository.registerImportedProfile(profile);
 		} catch (error) {
 			await profileRepository.deleteProfileDir(profilePath);
 			throw error;
@@ -166,30 +142,14 @@ class ProfileWorkflowService {
 	}
 
 	async deleteProfile(profileId: string): Promise<void> {
-		const profile = await this.getProfileById(profileId);
-		if (!profile) throw new Error(`Profile '${profileId}' not found`);
-		await profileRepository.deleteProfileDir(profile.path);
+		await profileRepository.deleteProfile(profileId);
 	}
 
 	async renameProfile(profileId: string, newName: string): Promise<void> {
 		const trimmed = newName.trim();
 		if (!trimmed) throw new Error('Profile name cannot be empty');
 
-		const profiles = await this.getProfiles();
-		const profile = profiles.find((candidate) => candidate.id === profileId);
-		if (!profile) throw new Error(`Profile '${profileId}' not found`);
-
-		if (
-			profiles.some(
-				(candidate) =>
-					candidate.id !== profileId && candidate.name.toLowerCase() === trimmed.toLowerCase()
-			)
-		) {
-			throw new Error(`Profile '${trimmed}' already exists`);
-		}
-
-		profile.name = trimmed;
-		await profileRepository.writeMetadata(profile);
+		await profileRepository.renameProfile(profileId, trimmed);
 	}
 
 	async updateProfileIcon(profileId: string, selection: ProfileIconSelection): Promise<void> {
@@ -256,13 +216,6 @@ class ProfileWorkflowService {
 		});
 	}
 
-	async updateLastLaunched(profileId: string): Promise<void> {
-		const profile = await this.getProfileById(profileId);
-		if (!profile) return;
-		profile.last_launched_at = Date.now();
-		await profileRepository.writeMetadata(profile);
-	}
-
 	async addModToProfile(
 		profileId: string,
 		modId: string,
@@ -281,13 +234,6 @@ class ProfileWorkflowService {
 		await profileRepository.writeMetadata(profile);
 	}
 
-	async addPlayTime(profileId: string, durationMs: number): Promise<void> {
-		const profile = await this.getProfileById(profileId);
-		if (!profile) throw new Error(`Profile '${profileId}' not found`);
-		profile.total_play_time = (profile.total_play_time ?? 0) + durationMs;
-		await profileRepository.writeMetadata(profile);
-	}
-
 	async removeModFromProfile(profileId: string, modId: string): Promise<void> {
 		const profile = await this.getProfileById(profileId);
 		if (!profile) throw new Error(`Profile '${profileId}' not found`);
@@ -296,6 +242,21 @@ class ProfileWorkflowService {
 		await profileRepository.writeMetadata(profile);
 	}
 
+	/**
+	 * Takes one restorable snapshot before a batch of mod changes. Failures are
+	 * logged so they never block the changes themselves.
+	 */
+	async snapshotBeforeModChanges(
+		profilePath: string,
+		reason: 'before_mod_install' | 'before_mod_removal'
+	): Promise<void> {
+		try {
+			await profilePlatformAdapter.snapshotBatch({ profilePath, reason });
+		} catch (error) {
+			logError(`Failed to snapshot ${profilePath} before mod changes: ${error}`);
+		}
+	}
+
 	readonly getModFiles = (profilePath: string) => profileRepository.getModFiles(profilePath);
 
 	async countMods(profilePath: string): Promise<number> {
@@ -365,6 +326,7 @@ class ProfileWorkflowService {
 	async deleteUnifiedMod(profileId: string, mod: UnifiedMod): Promise<void> {
 		const profile = await this.getProfileById(profileId);
 		if (!profile) throw new Error(`Profile '${profileId}' not found`);
+		await this.snapshotBeforeModChanges(profile.path, 'before_mod_removal');
 		await this.deleteModFile(profile.path, mod.file);
 		if (mod.source === 'managed') {
 			await this.removeModFromProfile(profileId, mod.mod_id);
@@ -7,6 +7,7 @@ export const ProfileModEntry = type({
 });
 
 export const ProfileEntry = type({
+	'schema_version?': 'number',
 	id: 'string',
 	name: 'string <= 100',
 	path: 'string',
@@ -34,7 +35,7 @@ export type UnifiedMod =
 
 // Progress types for BepInEx installation
 export interface BepInExProgress {
-	stage: 'downloading' | 'extracting' | 'complete';
+	stage: 'downloading' | 'retrying' | 'extracting' | 'complete';
 	progress: number;
 	message: string;
 }
@@ -45,5 +46,5 @@ export interface ModDownloadProgress {
 	downloaded: number;
 	total: number | null;
 	progress: number; // 0-100
-	stage: 'connecting' | 'downloading' | 'verifying' | 'writing' | 'complete';
+	stage: 'connecting' | 'downloading' | 'retrying' | 'verifying' | 'writing' | 'complete';
 }
@@ -1,17 +1,30 @@
+import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
-import { profileWorkflowService } from '../profile-workflow-service';
+import { warn } from '@tauri-apps/plugin-log';
 import { notifyProfilesInvalidated } from './profile-invalidation-bridge';
 
+export interface GameInstance {
+	/** Unknown for Xbox instances, which are started through the shell. */
+	pid: number | null;
+	profile_id: string | null;
+	platform: string;
+	kind: 'modded' | 'vanilla' | 'uwp';
+	/** Unix time in milliseconds. */
+	launched_at: number;
+}
+
 interface GameStatePayload {
 	running: boolean;
 	running_count?: number;
 	profile_instance_counts?: Record<string, number>;
+	instances?: GameInstance[];
 }
 
 class GameRuntimeStateStore {
 	#running = $state(false);
 	#runningCount = $state(0);
 	#profileInstanceCounts = $state<Record<string, number>>({});
+	#instances = $state<GameInstance[]>([]);
 	#runningProfileId = $state<string | null>(null);
 	#sessionStartTime = $state<number | null>(null);
 	#currentTime = $state(Date.now());
@@ -34,20 +47,20 @@ class GameRuntimeStateStore {
 		return this.getProfileRunningInstanceCount(profileId) > 0;
 	}
 
+	get instances(): GameInstance[] {
+		return this.#instances;
+	}
+
+	getProfileInstances(profileId: string): GameInstance[] {
+		return this.#instances.filter((instance) => instance.profile_id === profileId);
+	}
+
 	getSessionDuration(): number {
 		if (!this.#sessionStartTime) return 0;
 		return this.#currentTime - this.#sessionStartTime;
 	}
 
-	private async finalizeSession() {
-		if (this.#running && this.#runningProfileId) {
-			const duration = this.getSessionDuration();
-			if (duration > 0) {
-				await profileWorkflowService.addPlayTime(this.#runningProfileId, duration);
-				notifyProfilesInvalidated();
-			}
-		}
-
+	private finalizeSession() {
 		this.#sessionStartTime = null;
 		if (this.#interval) {
 			clearInterval(this.#interval);
@@ -55,10 +68,10 @@ class GameRuntimeStateStore {
 		}
 	}
 
-	private startTimer() {
+	private startTimer(launchedAt: number) {
 		if (this.#sessionStartTime) return;
-		this.#sessionStartTime = Date.now();
-		this.#currentTime = this.#sessionStartTime;
+		this.#sessionStartTime = launchedAt;
+		this.#currentTime = Date.now();
 		if (!this.#interval) {
 			this.#interval = setInterval(() => {
 				this.#currentTime = Date.now();
@@ -66,32 +79,66 @@ class GameRuntimeStateStore {
 		}
 	}
 
+	private applyPayload(payload: GameStatePayload) {
+		const runningCount = payload.running_count ?? (payload.running ? 1 : 0);
+		// The backend records launches and play time before it reports the change.
+		const instancesChanged = runningCount !== this.#runningCount;
+		if (this.#running && !payload.running) {
+			this.finalizeSession();
+			this.#runningProfileId = null;
+		}
+
+		this.#running = payload.running;
+		this.#runningCount = runningCount;
+		this.#profileInstanceCounts = payload.profile_instance_counts ?? {};
+		this.#instances = payload.instances ?? [];
+		const runningProfileIds = Object.entries(this.#profileInstanceCounts)
+			.filter(([, count]) => count > 0)
+			.map(([profileId]) => profileId);
+		if (runningProfileIds.length === 0) {
+			this.#runningProfileId = null;
+		} else if (runningProfileIds.length === 1) {
+			this.#runningProfileId = runningProfileIds[0] ?? null;
+		} else if (this.#runningProfileId && !runningProfileIds.includes(this.#runningProfileId)) {
+			this.#runningProfileId = null;
+		}
+
+		if (this.#running && this.#sessionStartTime === null) {
+			const launchTimes = this.#instances.map((instance) => instance.launched_at);
+			this.startTimer(launchTimes.length > 0 ? Math.min(...launchTimes) : Date.now());
+		}
+
+		if (instancesChanged) {
+			notifyProfilesInvalidated();
+		}
+	}
+
 	async init() {
 		if (this.#unlisten) return;
-		this.#unlisten = await listen<GameStatePayload>('game-state-changed', async (event) => {
-			if (this.#running && !event.payload.running) {
-				await this.finalizeSession();
-				this.#runningProfileId = null;
-			}
-
-			this.#running = event.payload.running;
-			this.#runningCount = event.payload.running_count ?? (event.payload.running ? 1 : 0);
-			this.#profileInstanceCounts = event.payload.profile_instance_counts ?? {};
-			const runningProfileIds = Object.entries(this.#profileInstanceCounts)
-				.filter(([, count]) => count > 0)
-				.map(([profileId]) => profileId);
-			if (runningProfileIds.length === 0) {
-				this.#runningProfileId = null;
-			} else if (runningProfileIds.length === 1) {
-				this.#runningProfileId = runningProfileIds[0] ?? null;
-			} else if (this.#runningProfileId && !runningProfileIds.includes(this.#runningProfileId)) {
-				this.#runningProfileId = null;
-			}
+		this.#unlisten = await listen<GameStatePayload>('game-state-changed', (event) => {
+			this.applyPayload(event.payload);
+		});
 
-			if (this.#running && this.#sessionStartTime === null) {
-				this.startTimer();
+		// Games launched before the window was opened do not emit an event.
+		try {
+			const instances = await invoke<GameInstance[]>('game_list_instances');
+			if (instances.length > 0 && !this.#running) {
+				const counts: Record<string, number> = {};
+				for (const instance of instances) {
+					if (instance.profile_id) {
+						counts[instance.profile_id] = (counts[instance.profile_id] ?? 0) + 1;
+					}
+				}
+				this.applyPayload({
+					running: true,
+					running_count: instances.length,
+					profile_instance_counts: counts,
+					instances
+				});
 			}
-		});
+		} catch (error) {
+			warn(`Failed to list running game instances: ${error}`);
+		}
 	}
 
 	destroy() {
@@ -47,6 +47,8 @@ class ModDownloadStateStore {
 				return 'Connecting...';
 			case 'downloading':
 				return 'Downloading...';
+			case 'retrying':
+				return 'Retrying...';
 			case 'verifying':
 				return 'Verifying checksum...';
 			case 'writing':
@@ -9,7 +9,6 @@ import { showError, showSuccess } from '$lib/utils/toast';
 
 interface ProfileDetailControllerDeps {
 	launchProfile: (profile: Profile) => Promise<void>;
-	updateLastLaunched: (profileId: string) => Promise<void>;
 	deleteProfile: (profileId: string) => Promise<void>;
 	removeProfileQueries: (profileId: string) => void;
 	renameProfile: (profileId: string, newName: string) => Promise<void>;
@@ -18,10 +17,7 @@ interface ProfileDetailControllerDeps {
 
 export function createProfileDetailController(deps: ProfileDetailControllerDeps) {
 	return {
-		launchProfile: async (profile: Profile) => {
-			await deps.launchProfile(profile);
-			await deps.updateLastLaunched(profile.id);
-		},
+		launchProfile: (profile: Profile) => deps.launchProfile(profile),
 
 		openProfileFolder: async (profile: Profile) => {
 			try {